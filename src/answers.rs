@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::store::InputStore;
+
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+#[derive(Debug)]
+pub enum AnswerError {
+    Io(io::Error),
+    Parse { path: PathBuf, err: toml::de::Error },
+    Serialize(toml::ser::Error),
+}
+
+impl fmt::Display for AnswerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnswerError::Io(err) => write!(f, "io error: {err}"),
+            AnswerError::Parse { path, err } => {
+                write!(f, "could not parse {}: {err}", path.display())
+            }
+            AnswerError::Serialize(err) => write!(f, "could not serialize answers: {err}"),
+        }
+    }
+}
+
+impl Error for AnswerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AnswerError::Io(err) => Some(err),
+            AnswerError::Parse { err, .. } => Some(err),
+            AnswerError::Serialize(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for AnswerError {
+    fn from(err: io::Error) -> Self {
+        AnswerError::Io(err)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PartAnswers {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    part1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    part2: Option<String>,
+}
+
+impl PartAnswers {
+    fn part_mut(&mut self, part: u32) -> &mut Option<String> {
+        match part {
+            1 => &mut self.part1,
+            2 => &mut self.part2,
+            _ => panic!("There is no part {part}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Check {
+    Unknown,
+    Correct,
+    Wrong { expected: String },
+}
+
+/// Known answers of one day, stored next to its inputs as `answers.toml` with a table per variant:
+///
+/// ```toml
+/// [input]
+/// part1 = "525119"
+/// part2 = "76504829"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers {
+    path: PathBuf,
+    variants: BTreeMap<String, PartAnswers>,
+}
+
+impl Answers {
+    /// A missing file is the same as no recorded answers
+    pub fn load(store: &InputStore, year: i32, day: u32) -> Result<Self, AnswerError> {
+        let path = store.day_dir(year, day).join(ANSWERS_FILE);
+        let variants = match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).map_err(|err| AnswerError::Parse {
+                path: path.clone(),
+                err,
+            })?,
+            Err(err) if err.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self { path, variants })
+    }
+
+    pub fn save(&self) -> Result<(), AnswerError> {
+        let contents = toml::to_string(&self.variants).map_err(AnswerError::Serialize)?;
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(fs::write(&self.path, contents)?)
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn get(&self, variant: &str, part: u32) -> Option<&str> {
+        let answers = self.variants.get(variant)?;
+        match part {
+            1 => answers.part1.as_deref(),
+            2 => answers.part2.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, variant: &str, part: u32, answer: &str) {
+        *self
+            .variants
+            .entry(variant.to_string())
+            .or_default()
+            .part_mut(part) = Some(answer.to_string());
+    }
+
+    pub fn check(&self, variant: &str, part: u32, answer: &str) -> Check {
+        match self.get(variant, part) {
+            None => Check::Unknown,
+            Some(expected) if expected == answer => Check::Correct,
+            Some(expected) => Check::Wrong {
+                expected: expected.to_string(),
+            },
+        }
+    }
+}
+
+#[test]
+fn answers_round_trip() {
+    let root = std::env::temp_dir().join(format!("aoc-2023-answers-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let store = InputStore::new(&root);
+
+    let mut answers = Answers::load(&store, 2022, 7).unwrap();
+    assert_eq!(answers.check("input", 1, "95437"), Check::Unknown);
+
+    answers.set("input", 1, "95437");
+    answers.set("example", 2, "24933642");
+    answers.save().unwrap();
+    assert_eq!(answers.path(), &root.join("2022/day7/answers.toml"));
+
+    let answers = Answers::load(&store, 2022, 7).unwrap();
+    assert_eq!(answers.check("input", 1, "95437"), Check::Correct);
+    assert_eq!(
+        answers.check("input", 1, "1"),
+        Check::Wrong {
+            expected: "95437".to_string()
+        }
+    );
+    assert_eq!(answers.check("input", 2, "1"), Check::Unknown);
+    assert_eq!(answers.get("example", 2), Some("24933642"));
+
+    fs::write(answers.path(), "[input]\npart1 = 95437\n").unwrap();
+    assert!(matches!(
+        Answers::load(&store, 2022, 7),
+        Err(AnswerError::Parse { .. })
+    ));
+
+    fs::remove_dir_all(&root).unwrap();
+}
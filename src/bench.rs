@@ -0,0 +1,193 @@
+use std::fmt;
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchConfig {
+    /// Untimed runs before measuring, to warm up caches and the allocator
+    pub warmup: u32,
+    pub reps: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Part1 => write!(f, "part1"),
+            Stage::Part2 => write!(f, "part2"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub reps: u32,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Stats {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let reps = samples.len() as u32;
+        let mean = samples.iter().sum::<Duration>() / reps.max(1);
+        let median = match samples.len() {
+            0 => Duration::ZERO,
+            n if n % 2 == 0 => (samples[n / 2 - 1] + samples[n / 2]) / 2,
+            n => samples[n / 2],
+        };
+        Self {
+            reps,
+            min: samples.first().copied().unwrap_or_default(),
+            median,
+            mean,
+        }
+    }
+}
+
+/// Runs `f` `warmup` times untimed and then `reps` times timed, returning the last result
+pub fn measure<T>(config: &BenchConfig, mut f: impl FnMut() -> T) -> (T, Stats) {
+    for _ in 0..config.warmup {
+        black_box(f());
+    }
+    let mut samples = Vec::with_capacity(config.reps as usize);
+    let mut result = None;
+    for _ in 0..config.reps.max(1) {
+        let start = Instant::now();
+        let value = black_box(f());
+        samples.push(start.elapsed());
+        result = Some(value);
+    }
+    (result.unwrap(), Stats::from_samples(samples))
+}
+
+/// One row of a report, durations are in nanoseconds so exports stay easy to diff and plot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Timing {
+    pub day: u32,
+    pub stage: Stage,
+    pub reps: u32,
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub mean_ns: u64,
+}
+
+impl Timing {
+    pub fn new(day: u32, stage: Stage, stats: &Stats) -> Self {
+        Self {
+            day,
+            stage,
+            reps: stats.reps,
+            min_ns: stats.min.as_nanos() as u64,
+            median_ns: stats.median.as_nanos() as u64,
+            mean_ns: stats.mean.as_nanos() as u64,
+        }
+    }
+}
+
+fn format_ns(ns: u64) -> String {
+    format!("{:.2?}", Duration::from_nanos(ns))
+}
+
+pub fn render_table(timings: &[Timing]) -> String {
+    let mut table = format!(
+        "{:>3}  {:<5}  {:>4}  {:>10}  {:>10}  {:>10}\n",
+        "day", "stage", "reps", "min", "median", "mean"
+    );
+    for timing in timings {
+        table.push_str(&format!(
+            "{:>3}  {:<5}  {:>4}  {:>10}  {:>10}  {:>10}\n",
+            timing.day,
+            timing.stage.to_string(),
+            timing.reps,
+            format_ns(timing.min_ns),
+            format_ns(timing.median_ns),
+            format_ns(timing.mean_ns)
+        ));
+    }
+    let total = timings.iter().map(|timing| timing.median_ns).sum();
+    table.push_str(&format!("total (median): {}\n", format_ns(total)));
+    table
+}
+
+pub fn write_json(path: &Path, timings: &[Timing]) -> io::Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    Ok(serde_json::to_writer_pretty(writer, timings)?)
+}
+
+pub fn write_csv(path: &Path, timings: &[Timing]) -> Result<(), csv::Error> {
+    let mut writer = csv::Writer::from_path(path)?;
+    for timing in timings {
+        writer.serialize(timing)?;
+    }
+    Ok(writer.flush()?)
+}
+
+#[test]
+fn measure_and_export() {
+    let config = BenchConfig { warmup: 2, reps: 5 };
+    let mut calls = 0;
+    let (result, stats) = measure(&config, || {
+        calls += 1;
+        calls
+    });
+    assert_eq!((result, calls), (7, 7));
+    assert_eq!(stats.reps, 5);
+    assert!(stats.min <= stats.median && stats.min <= stats.mean);
+
+    let stats = Stats::from_samples(
+        [4, 1, 3, 2]
+            .into_iter()
+            .map(Duration::from_micros)
+            .collect(),
+    );
+    assert_eq!(stats.min, Duration::from_micros(1));
+    assert_eq!(
+        stats.median,
+        Duration::from_micros(2) + Duration::from_nanos(500)
+    );
+    assert_eq!(
+        stats.mean,
+        Duration::from_micros(2) + Duration::from_nanos(500)
+    );
+
+    let timings = vec![
+        Timing::new(3, Stage::Parse, &stats),
+        Timing::new(3, Stage::Part2, &stats),
+    ];
+    let table = render_table(&timings);
+    assert!(table
+        .lines()
+        .nth(2)
+        .unwrap()
+        .starts_with("  3  part2     4"));
+    assert!(table.ends_with("total (median): 5.00µs\n"));
+
+    let dir = std::env::temp_dir().join(format!("aoc-2023-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    write_csv(&dir.join("bench.csv"), &timings).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(dir.join("bench.csv")).unwrap(),
+        "day,stage,reps,min_ns,median_ns,mean_ns\n3,parse,4,1000,2500,2500\n3,part2,4,1000,2500,2500\n"
+    );
+    write_json(&dir.join("bench.json"), &timings).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("bench.json")).unwrap()).unwrap();
+    assert_eq!(json[1]["stage"], "part2");
+    assert_eq!(json[0]["median_ns"], 2500);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,15 +1,15 @@
-extern crate test;
+//! [Day 1: Trebuchet?!](https://adventofcode.com/2023/day/1)
 
 use circular_buffer::CircularBuffer;
 use itertools::Either;
 use std::borrow::Cow;
 
-#[cfg(test)]
-use test::Bencher;
+use crate::parse::{parse_lines, ParseError};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
-use crate::utils::read_input_to_string;
-
-fn part1(input: &str) -> u32 {
+pub fn part1(input: &str) -> u32 {
     let mut acc = 0;
     for line in input.lines() {
         let first = line
@@ -88,7 +88,7 @@ where
     0
 }
 
-fn part2(input: &str) -> u32 {
+pub fn part2(input: &str) -> u32 {
     let mut acc = 0;
 
     for line in input.lines() {
@@ -106,12 +106,31 @@ fn part2(input: &str) -> u32 {
     acc
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(1)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input<'a> = &'a str;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    /// Checks that every line has a digit for part 1
+    fn parse<'a>(&self, input: &'a str) -> Result<&'a str, ParseError> {
+        parse_lines(input, |line| {
+            match line.contains(|c: char| c.is_ascii_digit()) {
+                true => Ok(()),
+                false => Err(ParseError::missing("digit")),
+            }
+        })?;
+        Ok(input)
+    }
+
+    fn part1(&self, input: &&str) -> u32 {
+        part1(input)
+    }
 
-    Ok(())
+    fn part2(&self, input: &&str) -> Option<u32> {
+        Some(part2(input))
+    }
 }
 
 #[test]
@@ -121,6 +140,9 @@ pqr3stu8vwx
 a1b2c3d4e5f
 treb7uchet";
     assert_eq!(part1(input), 142);
+
+    let err = Day1.parse(&format!("{input}\nabc")).unwrap_err();
+    assert_eq!(err.to_string(), "line 5: missing digit");
 }
 
 #[test]
@@ -139,18 +161,10 @@ ztchfjrmpgsevenzsjqzmsjj8ninehrsbgknine
     assert_eq!(part2(input), 281 + 54 + 79 + 91 + 17);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(1).unwrap();
-    assert_eq!(part1(input), 56049);
-    assert_eq!(part2(input), 54530);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(1).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(1, 1, part1(input));
+    assert_answer(1, 2, part2(input));
 }
@@ -1,123 +1,18 @@
-extern crate test;
+//! [Day 10: Pipe Maze](https://adventofcode.com/2023/day/10)
 
 use itertools::Itertools;
 use std::collections::HashSet;
-use std::ops::{Add, AddAssign, Sub};
 
-use ndarray::Array2;
-#[cfg(test)]
-use test::Bencher;
+use crate::parse::ParseError;
+use crate::solution::Solution;
+use crate::utils::grid::{parse_grid, Direction, Grid, Position};
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
+use crate::visualize::{Rgb, Scene};
+#[cfg(all(test, not(target_arch = "wasm32")))]
+use proptest::prelude::*;
 
-use crate::utils::{parse_board, pretty_string, read_input_to_string};
-
-const DIRS: [Direction; 4] = [
-    Direction::Up,
-    Direction::Left,
-    Direction::Down,
-    Direction::Right,
-];
-
-type Board = Array2<char>;
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Position {
-    x: usize,
-    y: usize,
-}
-
-impl Position {
-    fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
-    }
-
-    fn to_index(self) -> [usize; 2] {
-        [self.y, self.x]
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Left,
-    Down,
-    Right,
-}
-
-impl Direction {
-    fn is_perpendicular(&self, other: Direction) -> bool {
-        let (a, b) = match self {
-            Direction::Up => (Direction::Left, Direction::Right),
-            Direction::Left => (Direction::Up, Direction::Down),
-            Direction::Down => (Direction::Left, Direction::Right),
-            Direction::Right => (Direction::Up, Direction::Down),
-        };
-        other == a || other == b
-    }
-}
-
-impl Add<Direction> for Position {
-    type Output = Position;
-
-    fn add(self, dir: Direction) -> Position {
-        match dir {
-            Direction::Up => Position {
-                x: self.x,
-                y: self.y - 1,
-            },
-            Direction::Left => Position {
-                x: self.x - 1,
-                y: self.y,
-            },
-            Direction::Down => Position {
-                x: self.x,
-                y: self.y + 1,
-            },
-            Direction::Right => Position {
-                x: self.x + 1,
-                y: self.y,
-            },
-        }
-    }
-}
-
-impl AddAssign<Direction> for Position {
-    fn add_assign(&mut self, other: Direction) {
-        *self = *self + other;
-    }
-}
-
-impl Add for Position {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self::Output {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
-        }
-    }
-}
-
-impl Sub for Position {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self::Output {
-        Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-        }
-    }
-}
-
-impl AddAssign for Position {
-    fn add_assign(&mut self, other: Self) {
-        *self = *self + other;
-    }
-}
-
-fn to_2d_pos(pos_1d: usize, line_length: usize) -> Position {
-    let y = pos_1d / line_length;
-    Position::new(pos_1d - y * line_length - y, y)
-}
+type Board = Grid<char>;
 
 fn pipe_char_to_directions(c: char) -> Option<(Direction, Direction)> {
     match c {
@@ -153,58 +48,25 @@ fn traverse_pipe(incoming: Direction, (d1, d2): (Direction, Direction)) -> Direc
     }
 }
 
-fn opposite_direction(direction: Direction) -> Direction {
-    match direction {
-        Direction::Up => Direction::Down,
-        Direction::Down => Direction::Up,
-        Direction::Left => Direction::Right,
-        Direction::Right => Direction::Left,
-    }
-}
-
 fn pipe_step(pos: Position, entry_dir: Direction, board: &Board) -> (Position, Direction) {
-    if let Some(pipe) = pipe_char_to_directions(board[pos.to_index()]) {
+    if let Some(pipe) = pipe_char_to_directions(board[pos]) {
         let exit_direction = traverse_pipe(entry_dir, pipe);
-        (pos + exit_direction, opposite_direction(exit_direction))
+        (pos + exit_direction, exit_direction.opposite())
     } else {
-        panic!(
-            "Bad pipe char at Position {:?} {}",
-            pos,
-            board[pos.to_index()]
-        )
-    }
-}
-
-#[allow(dead_code)]
-fn visualize(board: &Board, inside: &[Position]) -> String {
-    let mut board = board.clone();
-
-    for pos in inside {
-        board[pos.to_index()] = 'I'
+        panic!("Bad pipe char at Position {:?} {}", pos, board[pos])
     }
-    pretty_string(&board.view())
 }
 
-fn part1(input: &str) -> usize {
-    let lines = input.lines().collect_vec();
-    let start_pos = to_2d_pos(input.find(|v| v == 'S').unwrap(), lines[0].len());
-    let board = parse_board(input);
-
-    let mut moves: Vec<(Position, Direction)> = DIRS
+fn loop_length(start_pos: Position, board: &Board) -> usize {
+    let mut moves: Vec<(Position, Direction)> = Direction::ALL
         .into_iter()
         .filter_map(|dir| {
-            if (start_pos.x == 0 && dir == Direction::Left)
-                || (start_pos.y == 0 && dir == Direction::Up)
-            {
-                return None;
-            }
-
             let new_pos = start_pos + dir;
-            if let Some(c) = board.get(new_pos.to_index()) {
+            if let Some(c) = board.get(new_pos) {
                 if let Some(pipe) = pipe_char_to_directions(*c) {
-                    let entry_dir = opposite_direction(dir);
+                    let entry_dir = dir.opposite();
                     if entry_dir == pipe.0 || entry_dir == pipe.1 {
-                        return Some((new_pos, opposite_direction(dir)));
+                        return Some((new_pos, dir.opposite()));
                     }
                 }
             }
@@ -216,7 +78,7 @@ fn part1(input: &str) -> usize {
     while moves.iter().duplicates_by(|(pos, _)| pos).next().is_none() {
         moves
             .iter_mut()
-            .for_each(|mov| *mov = pipe_step(mov.0, mov.1, &board));
+            .for_each(|mov| *mov = pipe_step(mov.0, mov.1, board));
         step += 1;
     }
     step
@@ -260,7 +122,7 @@ fn is_inside(position: Position, board: &Board, grid_dimensions: Position, dir:
     {
         position += dir;
         if let Some(entry_dir) = par_entry_dir {
-            if let Some(pipe) = pipe_char_to_directions(board[position.to_index()]) {
+            if let Some(pipe) = pipe_char_to_directions(board[position]) {
                 match (
                     entry_dir.is_perpendicular(pipe.0),
                     entry_dir.is_perpendicular(pipe.1),
@@ -268,13 +130,13 @@ fn is_inside(position: Position, board: &Board, grid_dimensions: Position, dir:
                     (true, true) => (),
                     (true, false) => {
                         par_entry_dir = None;
-                        if pipe.1 == opposite_direction(entry_dir) {
+                        if pipe.1 == entry_dir.opposite() {
                             intersections += 1;
                         }
                     }
                     (false, true) => {
                         par_entry_dir = None;
-                        if pipe.0 == opposite_direction(entry_dir) {
+                        if pipe.0 == entry_dir.opposite() {
                             intersections += 1;
                         }
                     }
@@ -285,10 +147,10 @@ fn is_inside(position: Position, board: &Board, grid_dimensions: Position, dir:
             } else {
                 panic!(
                     "wot should have pipes until we exit par, got {}",
-                    board[position.to_index()]
+                    board[position]
                 )
             }
-        } else if let Some(pipe) = pipe_char_to_directions(board[position.to_index()]) {
+        } else if let Some(pipe) = pipe_char_to_directions(board[position]) {
             match (dir.is_perpendicular(pipe.0), dir.is_perpendicular(pipe.1)) {
                 (true, true) => intersections += 1,
                 (true, false) => par_entry_dir = Some(pipe.0),
@@ -300,26 +162,17 @@ fn is_inside(position: Position, board: &Board, grid_dimensions: Position, dir:
     intersections % 2 != 0
 }
 
-fn part2(input: &str) -> usize {
-    let lines = input.lines().collect_vec();
-    let start_pos = to_2d_pos(input.find(|v| v == 'S').unwrap(), lines[0].len());
-    let board = parse_board(input);
-
-    let moves: Vec<(Position, Direction)> = DIRS
+/// The positions of the loop through the start and of the tiles it encloses
+fn loop_and_enclosed(start_pos: Position, board: &Board) -> (HashSet<Position>, Vec<Position>) {
+    let moves: Vec<(Position, Direction)> = Direction::ALL
         .into_iter()
         .filter_map(|dir| {
-            if (start_pos.x == 0 && dir == Direction::Left)
-                || (start_pos.y == 0 && dir == Direction::Up)
-            {
-                return None;
-            }
-
             let new_pos = start_pos + dir;
-            if let Some(c) = board.get(new_pos.to_index()) {
+            if let Some(c) = board.get(new_pos) {
                 if let Some(pipe) = pipe_char_to_directions(*c) {
-                    let entry_dir = opposite_direction(dir);
+                    let entry_dir = dir.opposite();
                     if entry_dir == pipe.0 || entry_dir == pipe.1 {
-                        return Some((new_pos, opposite_direction(dir)));
+                        return Some((new_pos, dir.opposite()));
                     }
                 }
             }
@@ -345,7 +198,7 @@ fn part2(input: &str) -> usize {
     {
         moves.iter_mut().for_each(|v| {
             let mov = v.last().unwrap();
-            v.push(pipe_step(mov.0, mov.1, &board));
+            v.push(pipe_step(mov.0, mov.1, board));
         });
     }
 
@@ -364,7 +217,7 @@ fn part2(input: &str) -> usize {
         let mut b_moves = b_moves
             .into_iter()
             .rev()
-            .map(|(pos, dir)| (pos, opposite_direction(dir)))
+            .map(|(pos, dir)| (pos, dir.opposite()))
             .collect_vec();
         a_moves.append(&mut b_moves);
         a_moves
@@ -372,55 +225,89 @@ fn part2(input: &str) -> usize {
         panic!("wut")
     };
 
-    let mut board = board;
+    let mut board = board.clone();
 
-    board[start_pos.to_index()] = directions_to_pipe_char((
-        opposite_direction(cycle.first().unwrap().1),
-        cycle.last().unwrap().1,
-    ))
-    .unwrap();
+    board[start_pos] =
+        directions_to_pipe_char((cycle.first().unwrap().1.opposite(), cycle.last().unwrap().1))
+            .unwrap();
 
     let mut cycle_set: HashSet<Position> = cycle.into_iter().map(|(pos, _)| pos).collect();
     cycle_set.insert(start_pos);
 
-    for (idx, v) in board.indexed_iter_mut() {
-        if !cycle_set.contains(&Position::new(idx.1, idx.0)) {
+    for (pos, v) in board.iter_mut() {
+        if !cycle_set.contains(&pos) {
             *v = '.';
         }
     }
 
+    let grid_dimensions = Position::new(board.width() as isize, board.height() as isize);
     let inside_positions = board
-        .indexed_iter()
+        .iter()
         .filter_map(|(pos, c)| {
             if *c == '.'
                 && is_inside(
-                    Position::new(pos.1, pos.0),
+                    pos,
                     &board,
-                    Position::new(board.dim().1, board.dim().0),
-                    direction_to_nearest_edge(
-                        Position::new(pos.1, pos.0),
-                        Position::new(board.dim().1, board.dim().0),
-                    ),
+                    grid_dimensions,
+                    direction_to_nearest_edge(pos, grid_dimensions),
                 )
             {
-                Some(Position::new(pos.1, pos.0))
+                Some(pos)
             } else {
                 None
             }
         })
         .collect_vec();
 
-    // println!("{}", visualize(&board, &inside_positions));
+    (cycle_set, inside_positions)
+}
 
-    inside_positions.len()
+pub struct Sketch {
+    start_pos: Position,
+    board: Board,
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(10)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub struct Day10;
 
-    Ok(())
+impl Solution for Day10 {
+    type Input<'a> = Sketch;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(&self, input: &str) -> Result<Sketch, ParseError> {
+        let board: Board = parse_grid(input, |c| "|-LJ7F.S".contains(c).then_some(c))?;
+        Ok(Sketch {
+            start_pos: board
+                .position(|c| *c == 'S')
+                .ok_or_else(|| ParseError::missing("start 'S'"))?,
+            board,
+        })
+    }
+
+    fn part1(&self, sketch: &Sketch) -> usize {
+        loop_length(sketch.start_pos, &sketch.board)
+    }
+
+    fn part2(&self, sketch: &Sketch) -> Option<usize> {
+        Some(loop_and_enclosed(sketch.start_pos, &sketch.board).1.len())
+    }
+
+    fn scene(&self, sketch: &Sketch) -> Option<Scene> {
+        let (cycle, inside) = loop_and_enclosed(sketch.start_pos, &sketch.board);
+        Some(
+            Scene::new(sketch.board.cells().clone())
+                .layer("loop", Rgb::YELLOW, None, cycle)
+                .layer("enclosed", Rgb::GREEN, Some('I'), inside),
+        )
+    }
+}
+
+pub fn part1(input: &str) -> usize {
+    Day10.part1(&Day10.parse(input).unwrap())
+}
+
+pub fn part2(input: &str) -> usize {
+    Day10.part2(&Day10.parse(input).unwrap()).unwrap()
 }
 
 #[test]
@@ -487,18 +374,112 @@ L7JLJL-JLJLJL--JLJ.L";
     assert_eq!(part2(input), 10);
 }
 
+#[cfg(all(test, not(target_arch = "wasm32")))]
+const FILLER: [char; 7] = ['|', '-', 'L', 'J', '7', 'F', '.'];
+
+/// A loop around a random shape of up to 5x5 tiles whose columns have no gaps and overlap their
+/// neighbors, drawn at twice the scale so the tiles it encloses stay apart from it, with random
+/// pipes everywhere else. Comes with the distance to the farthest pipe and the enclosed count
+#[cfg(all(test, not(target_arch = "wasm32")))]
+fn pipe_loop() -> impl Strategy<Value = (String, usize, usize)> {
+    (
+        1..=5_usize,
+        1..=5_usize,
+        prop::collection::vec((0..5_usize, 0..5_usize), 5),
+        prop::collection::vec(prop::sample::select(FILLER.to_vec()), 11 * 11),
+        any::<prop::sample::Index>(),
+    )
+        .prop_map(|(width, height, columns, filler, start)| {
+            let mut spans: Vec<(usize, usize)> = vec![];
+            for &(top, len) in &columns[..width] {
+                let top = top % height;
+                let (mut top, mut bottom) = (top, (top + len).min(height - 1));
+                if let Some(&(prev_top, prev_bottom)) = spans.last() {
+                    top = top.min(prev_bottom);
+                    bottom = bottom.max(prev_top);
+                }
+                spans.push((top, bottom));
+            }
+            let in_shape = |x: isize, y: isize| {
+                x >= 0
+                    && y >= 0
+                    && spans
+                        .get(x as usize)
+                        .is_some_and(|&(top, bottom)| (top..=bottom).contains(&(y as usize)))
+            };
+            // Whether each of the up to 4 tiles a position is part of is in the shape
+            let touching = |pos: Position| {
+                itertools::iproduct!(
+                    [(pos.x - 1).div_euclid(2), pos.x.div_euclid(2)],
+                    [(pos.y - 1).div_euclid(2), pos.y.div_euclid(2)]
+                )
+                .map(|(x, y)| in_shape(x, y))
+                .collect_vec()
+            };
+            let on_loop = |pos: Position| {
+                let touching = touching(pos);
+                touching.contains(&true) && touching.contains(&false)
+            };
+
+            let mut board = Grid::from_elem(2 * width + 1, 2 * height + 1, '.');
+            let mut filler = filler.into_iter();
+            let mut loop_len = 0;
+            let mut enclosed = 0;
+            for (pos, c) in board.iter_mut() {
+                if on_loop(pos) {
+                    let dirs = Direction::ALL.into_iter().filter(|dir| on_loop(pos + *dir));
+                    *c = directions_to_pipe_char(dirs.collect_tuple().unwrap()).unwrap();
+                    loop_len += 1;
+                } else {
+                    *c = filler.next().unwrap();
+                    if !touching(pos).contains(&false) {
+                        enclosed += 1;
+                    }
+                }
+            }
+
+            let pipes = board
+                .iter()
+                .filter(|(pos, _)| on_loop(*pos))
+                .map(|(pos, _)| pos);
+            let start_pos = start.get(&pipes.collect_vec()).to_owned();
+            board[start_pos] = 'S';
+            // Only the loop may connect to the start
+            for dir in Direction::ALL {
+                let next = start_pos + dir;
+                if let Some(c) = board.get_mut(next).filter(|_| !on_loop(next)) {
+                    if pipe_char_to_directions(*c)
+                        .is_some_and(|(a, b)| a == dir.opposite() || b == dir.opposite())
+                    {
+                        *c = '.';
+                    }
+                }
+            }
+
+            let input = board
+                .cells()
+                .rows()
+                .into_iter()
+                .map(|row| row.iter().collect::<String>())
+                .join("\n");
+            (input, loop_len / 2, enclosed)
+        })
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+proptest! {
+    #[test]
+    fn matches_generated_loop((input, farthest, enclosed) in pipe_loop()) {
+        let sketch = Day10.parse(&input).unwrap();
+        prop_assert_eq!(Day10.part1(&sketch), farthest);
+        prop_assert_eq!(Day10.part2(&sketch), Some(enclosed));
+    }
+}
+
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(10).unwrap();
-    assert_eq!(part1(input), 6815);
-    assert_eq!(part2(input), 269);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(10).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(10, 1, part1(input));
+    assert_answer(10, 2, part2(input));
 }
@@ -1,13 +1,15 @@
-extern crate test;
+//! [Day 11: Cosmic Expansion](https://adventofcode.com/2023/day/11)
 
 use itertools::Itertools;
 use std::collections::HashSet;
 
 use ndarray::{Array2, Axis, Dim};
-#[cfg(test)]
-use test::Bencher;
 
-use crate::utils::{parse_board, read_input_to_string};
+use crate::parse::ParseError;
+use crate::solution::Solution;
+use crate::utils::grid::parse_grid;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 type Board = Array2<char>;
 
@@ -26,9 +28,8 @@ impl Position {
         [self.y, self.x]
     }
 
-    fn manhattan(&self, other: &Self) -> usize {
-        ((self.x as isize - other.x as isize).abs() + (self.y as isize - other.y as isize).abs())
-            as usize
+    fn manhattan(&self, other: &Self) -> u64 {
+        (self.x.abs_diff(other.x) + self.y.abs_diff(other.y)) as u64
     }
 }
 
@@ -83,10 +84,9 @@ fn pad_board(stars: &HashSet<Position>, board: &Board, extra_height: usize, extr
     }
 }
 
-fn parts(input: &str, multiplier: usize) -> usize {
-    let board = parse_board(input);
-    let stars = get_stars(&board);
-    let stars = expand_stars(&stars, &board, multiplier);
+fn parts(board: &Board, multiplier: usize) -> u64 {
+    let stars = get_stars(board);
+    let stars = expand_stars(&stars, board, multiplier);
 
     stars
         .iter()
@@ -95,20 +95,32 @@ fn parts(input: &str, multiplier: usize) -> usize {
         .sum()
 }
 
-fn part1(input: &str) -> usize {
-    parts(input, 1)
-}
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input<'a> = Board;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(&self, input: &str) -> Result<Board, ParseError> {
+        Ok(parse_grid(input, |c| "#.".contains(c).then_some(c))?.into_cells())
+    }
+
+    fn part1(&self, board: &Board) -> u64 {
+        parts(board, 1)
+    }
 
-fn part2(input: &str) -> usize {
-    parts(input, 1000000 - 1)
+    fn part2(&self, board: &Board) -> Option<u64> {
+        Some(parts(board, 1000000 - 1))
+    }
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(11)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub fn part1(input: &str) -> u64 {
+    Day11.part1(&Day11.parse(input).unwrap())
+}
 
-    Ok(())
+pub fn part2(input: &str) -> u64 {
+    Day11.part2(&Day11.parse(input).unwrap()).unwrap()
 }
 
 #[test]
@@ -124,21 +136,13 @@ fn example() {
 .......#..
 #...#.....";
     assert_eq!(part1(input), 374);
-    assert_eq!(parts(input, 100 - 1), 8410);
+    assert_eq!(parts(&Day11.parse(input).unwrap(), 100 - 1), 8410);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(11).unwrap();
-    assert_eq!(part1(input), 9742154);
-    assert_eq!(part2(input), 411142919886);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(11).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(11, 1, part1(input));
+    assert_answer(11, 2, part2(input));
 }
@@ -1,4 +1,4 @@
-extern crate test;
+//! [Day 12: Hot Springs](https://adventofcode.com/2023/day/12)
 
 use itertools::Itertools;
 use std::collections::HashMap;
@@ -6,37 +6,35 @@ use std::ops::Range;
 
 use crate::day12::ChunkResult::{Candidate, ForcedFail, ForcedSucc, Skip};
 use crate::day12::StepResult::Complete;
-#[cfg(test)]
-use test::Bencher;
-
-use crate::utils::read_input_to_string;
-
-fn parse_line(line: &str) -> (Vec<char>, Vec<usize>) {
-    if let Some((record, control)) = line.split_whitespace().collect_tuple() {
-        let record: Vec<char> = "."
-            .chars()
-            .chain(record.chars())
-            .chain(".".chars())
-            .collect();
-        let control: Vec<usize> = control
-            .split(',')
-            .map(str::parse)
-            .collect::<Result<_, _>>()
-            .unwrap();
-        (record, control)
-    } else {
-        panic!("Bad input line {line}")
+use crate::parse::{number, parse_lines, split_once, ParseError, ParseResult};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
+#[cfg(all(test, not(target_arch = "wasm32")))]
+use proptest::prelude::*;
+
+/// A row of springs with the sizes of its groups of damaged springs
+type Row<'a> = (&'a str, Vec<usize>);
+
+fn parse_line(line: &str) -> ParseResult<Row<'_>> {
+    let (record, control) = split_once(line, " ", "[springs] [groups]")?;
+    if let Some(spring) = record.chars().find(|c| !".#?".contains(*c)) {
+        return Err(ParseError::unknown("spring", spring));
     }
+    let control = control.split(',').map(number).collect::<ParseResult<_>>()?;
+    Ok((record, control))
 }
 
-fn part1(input: &str) -> usize {
-    input
-        .lines()
-        .map(|line| {
-            let (record, control) = parse_line(line);
-            count(&record, &control)
-        })
-        .sum()
+/// The springs with an operational one added on both ends
+fn padded(record: &str) -> Vec<char> {
+    ".".chars()
+        .chain(record.chars())
+        .chain(".".chars())
+        .collect()
+}
+
+pub fn part1(input: &str) -> u64 {
+    Day12.part1(&Day12.parse(input).unwrap())
 }
 
 struct ContiguousBlockIter<'a> {
@@ -96,7 +94,7 @@ enum ChunkResult {
 }
 
 fn classify_chunk(chunk: &[char]) -> ChunkResult {
-    let contains_dot = chunk[1..chunk.len() - 1].iter().any(|&c| c == '.');
+    let contains_dot = chunk[1..chunk.len() - 1].contains(&'.');
     let is_first_char_damaged = chunk.get(1) == Some(&'#');
     let are_boundaries_valid = matches!(chunk.first(), Some('?') | Some('.'))
         && matches!(chunk.last(), Some('?') | Some('.'));
@@ -165,7 +163,7 @@ fn arrangement_positions(chars: &[char], damaged: usize) -> Vec<usize> {
 
 #[derive(Clone)]
 enum StepResult {
-    Complete(usize),
+    Complete(u64),
     Positions(Vec<usize>),
 }
 
@@ -193,8 +191,8 @@ fn step(input: &[char], start_idx: usize, damaged: &[usize]) -> StepResult {
     StepResult::Positions(positions)
 }
 
-fn count(input: &[char], damaged: &[usize]) -> usize {
-    let mut queue: HashMap<(usize, &[usize]), usize> = HashMap::new();
+fn count(input: &[char], damaged: &[usize]) -> u64 {
+    let mut queue: HashMap<(usize, &[usize]), u64> = HashMap::new();
     queue.insert((0, damaged), 1);
 
     let mut arrangements = 0;
@@ -207,7 +205,7 @@ fn count(input: &[char], damaged: &[usize]) -> usize {
                 StepResult::Positions(positions) => {
                     for pos in positions {
                         *queue
-                            .entry((start_idx + pos, &damaged.get(1..).unwrap_or(&[])))
+                            .entry((start_idx + pos, damaged.get(1..).unwrap_or(&[])))
                             .or_insert(0) += multiplier;
                     }
                 }
@@ -219,42 +217,47 @@ fn count(input: &[char], damaged: &[usize]) -> usize {
 
 #[allow(unstable_name_collisions)]
 fn repeat(input: &str, pad_char: char, n: usize) -> String {
-    std::iter::repeat(input)
-        .take(n)
+    std::iter::repeat_n(input, n)
         .intersperse(&pad_char.to_string())
         .collect()
 }
 
-fn repeat_input(input: &str, n: usize) -> String {
-    if let Some((record, control)) = input.split_whitespace().collect_tuple() {
-        format!("{} {}", repeat(record, '?', n), repeat(control, ',', n))
-    } else {
-        panic!("bad input {input}")
-    }
+pub fn part2(input: &str) -> u64 {
+    Day12.part2(&Day12.parse(input).unwrap()).unwrap()
 }
 
-fn part2(input: &str) -> usize {
-    input
-        .lines()
-        .map(|line| {
-            let (record, control) = parse_line(&repeat_input(line, 5));
-            count(&record, &control)
-        })
-        .sum()
-}
+pub struct Day12;
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(12)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+impl Solution for Day12 {
+    type Input<'a> = Vec<Row<'a>>;
+    type Answer1 = u64;
+    type Answer2 = u64;
 
-    Ok(())
+    fn parse<'a>(&self, input: &'a str) -> Result<Vec<Row<'a>>, ParseError> {
+        parse_lines(input, parse_line)
+    }
+
+    fn part1(&self, rows: &Vec<Row>) -> u64 {
+        rows.iter()
+            .map(|(record, control)| count(&padded(record), control))
+            .sum()
+    }
+
+    fn part2(&self, rows: &Vec<Row>) -> Option<u64> {
+        Some(
+            rows.iter()
+                .map(|(record, control)| {
+                    count(&padded(&repeat(record, '?', 5)), &control.repeat(5))
+                })
+                .sum(),
+        )
+    }
 }
 
 #[test]
 fn test_repeat() {
-    let input = ".# 1";
-    assert_eq!(repeat_input(input, 5), ".#?.#?.#?.#?.# 1,1,1,1,1")
+    assert_eq!(repeat(".#", '?', 5), ".#?.#?.#?.#?.#");
+    assert_eq!(repeat("1", ',', 5), "1,1,1,1,1");
 }
 
 #[test]
@@ -265,12 +268,11 @@ fn example() {
 ????.#...#... 4,1,1
 ????.######..#####. 1,6,5
 ?###???????? 3,2,1";
-    let arrangement_counts: Vec<usize> = input
-        .lines()
-        .map(|line| {
-            let (record, control) = parse_line(line);
-            count(&record, &control)
-        })
+    let arrangement_counts: Vec<u64> = Day12
+        .parse(input)
+        .unwrap()
+        .iter()
+        .map(|(record, control)| count(&padded(record), control))
         .collect();
     assert_eq!(arrangement_counts, vec![1, 4, 1, 1, 4, 10]);
     assert_eq!(part1(input), 21);
@@ -285,28 +287,124 @@ fn example_p2() {
 ????.#...#... 4,1,1
 ????.######..#####. 1,6,5
 ?###???????? 3,2,1";
-    let arrangement_counts: Vec<usize> = input
-        .lines()
-        .map(|line| {
-            let (record, control) = parse_line(&repeat_input(line, 5));
-            count(&record, &control)
-        })
+    let arrangement_counts: Vec<u64> = Day12
+        .parse(input)
+        .unwrap()
+        .iter()
+        .map(|(record, control)| count(&padded(&repeat(record, '?', 5)), &control.repeat(5)))
         .collect();
     assert_eq!(arrangement_counts, vec![1, 16384, 1, 16, 2500, 506250]);
 }
 
+/// Rows of up to `max_len` springs, with the groups either read off a random filled in row, so
+/// there is at least one arrangement, or random
+#[cfg(all(test, not(target_arch = "wasm32")))]
+fn spring_row(max_len: usize) -> impl Strategy<Value = String> {
+    let from_solution = (
+        prop::collection::vec(any::<bool>(), 1..=max_len),
+        prop::collection::vec(any::<bool>(), max_len),
+    )
+        .prop_filter_map("no damaged springs", |(damaged, unknown)| {
+            let groups = damaged
+                .split(|d| !d)
+                .map(<[bool]>::len)
+                .filter(|len| *len > 0)
+                .collect_vec();
+            let record: String = damaged
+                .iter()
+                .zip(unknown)
+                .map(|(damaged, unknown)| match (unknown, damaged) {
+                    (true, _) => '?',
+                    (false, true) => '#',
+                    (false, false) => '.',
+                })
+                .collect();
+            (!groups.is_empty()).then(|| format!("{record} {}", groups.iter().join(",")))
+        });
+    let random = (
+        prop::collection::vec(prop::sample::select(vec!['.', '#', '?']), 1..=max_len),
+        prop::collection::vec(1..=4usize, 1..=4),
+    )
+        .prop_map(|(record, groups)| {
+            format!(
+                "{} {}",
+                record.iter().collect::<String>(),
+                groups.iter().join(",")
+            )
+        });
+    prop_oneof![from_solution, random]
+}
+
+/// Follows every position `arrangement_positions` gives for each group one arrangement at a
+/// time, without the memoization of `count`
+#[cfg(all(test, not(target_arch = "wasm32")))]
+fn enumerated_count(input: &[char], start_idx: usize, damaged: &[usize]) -> u64 {
+    match step(input, start_idx, damaged) {
+        Complete(n) => n,
+        StepResult::Positions(positions) => positions
+            .iter()
+            .map(|pos| enumerated_count(input, start_idx + pos, &damaged[1..]))
+            .sum(),
+    }
+}
+
+/// Fills in the `?`s of `record` in every possible way and counts the rows whose groups of
+/// damaged springs are `groups`
+#[cfg(all(test, not(target_arch = "wasm32")))]
+fn assignment_count(record: &str, groups: &[usize]) -> u64 {
+    let unknown = record.chars().filter(|c| *c == '?').count();
+    let matching = (0..1_u32 << unknown).filter(|assignment| {
+        let mut bit = 0;
+        let row: String = record
+            .chars()
+            .map(|c| match c {
+                '?' => {
+                    bit += 1;
+                    match assignment >> (bit - 1) & 1 {
+                        1 => '#',
+                        _ => '.',
+                    }
+                }
+                c => c,
+            })
+            .collect();
+        row.split('.')
+            .filter(|group| !group.is_empty())
+            .map(str::len)
+            .eq(groups.iter().copied())
+    });
+    matching.count() as u64
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+proptest! {
+    #[test]
+    fn count_matches_assignments(row in spring_row(12)) {
+        let rows = Day12.parse(&row).unwrap();
+        let (record, groups) = &rows[0];
+        prop_assert_eq!(Day12.part1(&rows), assignment_count(record, groups));
+    }
+
+    #[test]
+    fn count_matches_enumeration(row in spring_row(12)) {
+        let rows = Day12.parse(&row).unwrap();
+        let (record, groups) = &rows[0];
+        prop_assert_eq!(Day12.part1(&rows), enumerated_count(&padded(record), 0, groups));
+    }
+
+    #[test]
+    fn unfolded_count_matches_enumeration(row in spring_row(3)) {
+        let rows = Day12.parse(&row).unwrap();
+        let (record, groups) = &rows[0];
+        let expected = enumerated_count(&padded(&repeat(record, '?', 5)), 0, &groups.repeat(5));
+        prop_assert_eq!(Day12.part2(&rows), Some(expected));
+    }
+}
+
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(12).unwrap();
-    assert_eq!(part1(input), 6488);
-    // assert_eq!(part2(input), 815364548481);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(12).unwrap();
-        // part1(input);
-        part2(input);
-    })
+    assert_answer(12, 1, part1(input));
+    assert_answer(12, 2, part2(input));
 }
@@ -1,11 +1,12 @@
-extern crate test;
+//! [Day 13: Point of Incidence](https://adventofcode.com/2023/day/13)
 
-use itertools::Itertools;
-use ndarray::{s, ArrayView2, Zip};
-#[cfg(test)]
-use test::Bencher;
+use ndarray::{s, Array2, ArrayView2, Zip};
 
-use crate::utils::{parse_board, read_input_to_string};
+use crate::parse::{parse_blocks, ParseError};
+use crate::solution::Solution;
+use crate::utils::grid::parse_grid;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 #[allow(dead_code)]
 fn pretty_string(arr: &ArrayView2<char>) -> String {
@@ -48,8 +49,7 @@ fn find_mirror(board: &ArrayView2<char>, diff_count: usize) -> usize {
     v
 }
 
-fn parts(input: &str, diff_count: usize) -> usize {
-    let boards = input.split("\n\n").map(parse_board).collect_vec();
+fn parts(boards: &[Array2<char>], diff_count: usize) -> usize {
     let mut val = 0;
 
     for board in boards {
@@ -60,20 +60,34 @@ fn parts(input: &str, diff_count: usize) -> usize {
     val
 }
 
-fn part1(input: &str) -> usize {
-    parts(input, 0)
-}
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input<'a> = Vec<Array2<char>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(&self, input: &str) -> Result<Vec<Array2<char>>, ParseError> {
+        parse_blocks(input, |block| {
+            Ok(parse_grid(block, |c| "#.".contains(c).then_some(c))?.into_cells())
+        })
+    }
+
+    fn part1(&self, boards: &Vec<Array2<char>>) -> usize {
+        parts(boards, 0)
+    }
 
-fn part2(input: &str) -> usize {
-    parts(input, 1)
+    fn part2(&self, boards: &Vec<Array2<char>>) -> Option<usize> {
+        Some(parts(boards, 1))
+    }
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(13)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub fn part1(input: &str) -> usize {
+    Day13.part1(&Day13.parse(input).unwrap())
+}
 
-    Ok(())
+pub fn part2(input: &str) -> usize {
+    Day13.part2(&Day13.parse(input).unwrap()).unwrap()
 }
 
 #[test]
@@ -97,18 +111,10 @@ fn example() {
     assert_eq!(part2(input), 400);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(13).unwrap();
-    assert_eq!(part1(input), 37025);
-    assert_eq!(part2(input), 32854);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(13).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(13, 1, part1(input));
+    assert_answer(13, 2, part2(input));
 }
@@ -1,4 +1,4 @@
-extern crate test;
+//! [Day 14: Parabolic Reflector Dish](https://adventofcode.com/2023/day/14)
 
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
@@ -6,10 +6,13 @@ use std::hash::{Hash, Hasher};
 
 use ahash::AHasher;
 use ndarray::{s, Array1, Array2, ArrayView1, ArrayView2, Axis};
-#[cfg(test)]
-use test::Bencher;
 
-use crate::utils::{parse_board, read_input_to_string, rot270};
+use crate::parse::ParseError;
+use crate::solution::Solution;
+use crate::utils::grid::parse_grid;
+use crate::utils::rot270;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 type Board = Array2<char>;
 
@@ -69,8 +72,8 @@ fn load(board: &Board) -> usize {
         .sum()
 }
 
-fn part1(input: &str) -> usize {
-    let mut board = parse_board(input);
+fn tilted_load(board: &Array2<char>) -> usize {
+    let mut board = board.clone();
     for mut lane in board.lanes_mut(Axis(0)) {
         let new_lane = slide(&lane.view());
         lane.assign(&new_lane);
@@ -94,9 +97,9 @@ fn array_key(arr: &ArrayView2<char>) -> u64 {
     hasher.finish()
 }
 
-fn part2(input: &str) -> usize {
+fn spun_load(board: &Array2<char>) -> usize {
     let num_spins = 1000000000;
-    let mut board = parse_board(input);
+    let mut board = board.clone();
     let mut initial_board = board.to_owned();
 
     let mut seen: HashMap<u64, usize> = HashMap::new();
@@ -125,12 +128,32 @@ fn part2(input: &str) -> usize {
     load(&initial_board)
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(14)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub struct Day14;
 
-    Ok(())
+impl Solution for Day14 {
+    type Input<'a> = Array2<char>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(&self, input: &str) -> Result<Array2<char>, ParseError> {
+        Ok(parse_grid(input, |c| ".O#".contains(c).then_some(c))?.into_cells())
+    }
+
+    fn part1(&self, board: &Array2<char>) -> usize {
+        tilted_load(board)
+    }
+
+    fn part2(&self, board: &Array2<char>) -> Option<usize> {
+        Some(spun_load(board))
+    }
+}
+
+pub fn part1(input: &str) -> usize {
+    Day14.part1(&Day14.parse(input).unwrap())
+}
+
+pub fn part2(input: &str) -> usize {
+    Day14.part2(&Day14.parse(input).unwrap()).unwrap()
 }
 
 #[test]
@@ -145,7 +168,7 @@ O..#.OO...
 ..O.......
 #....###..
 #....#....";
-    assert_eq!(load(&parse_board(input)), 136);
+    assert_eq!(load(&Day14.parse(input).unwrap()), 136);
 }
 
 #[test]
@@ -164,18 +187,10 @@ O.#..O.#.#
     assert_eq!(part2(input), 64);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(14).unwrap();
-    assert_eq!(part1(input), 109654);
-    assert_eq!(part2(input), 94876);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(14).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(14, 1, part1(input));
+    assert_answer(14, 2, part2(input));
 }
@@ -1,11 +1,12 @@
-extern crate test;
+//! [Day 15: Lens Library](https://adventofcode.com/2023/day/15)
 
 use std::collections::hash_map::Entry;
 use std::str::FromStr;
-#[cfg(test)]
-use test::Bencher;
 
-use crate::utils::read_input_to_string;
+use crate::parse::{number, ParseError};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 fn capital_hash(input: &str) -> usize {
     input.chars().fold(0, |mut acc, c| {
@@ -16,12 +17,15 @@ fn capital_hash(input: &str) -> usize {
     })
 }
 
-fn part1(input: &str) -> usize {
-    input.trim().split(',').map(capital_hash).sum()
+pub fn part1(input: &str) -> usize {
+    Day15.part1(&Day15.parse(input).unwrap())
 }
 
 type Boxes = [MapWithInsertionOrder];
 
+/// A step of the initialization sequence, hashed as written in part 1
+type Step<'a> = (&'a str, Op);
+
 fn focusing_power(boxes: &Boxes) -> impl Iterator<Item = usize> + '_ {
     boxes
         .iter()
@@ -36,27 +40,22 @@ fn focusing_power(boxes: &Boxes) -> impl Iterator<Item = usize> + '_ {
         })
 }
 
-#[derive(Debug, PartialEq)]
-enum Op {
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
     Sub(String),
     Eq(String, u8),
 }
 
 impl FromStr for Op {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        if let Some(eq_index) = value.find('=') {
-            let (label, focal) = value.split_at(eq_index);
-            if let Ok(focal) = focal[1..].parse::<u8>() {
-                Ok(Op::Eq(label.into(), focal))
-            } else {
-                Err(format!("bad eq focal {focal}"))
-            }
+        if let Some((label, focal)) = value.split_once('=') {
+            Ok(Op::Eq(label.into(), number(focal)?))
         } else if let Some(label) = value.strip_suffix('-') {
             Ok(Op::Sub(label.to_string()))
         } else {
-            Err(format!("bad op {value}"))
+            Err(ParseError::format("[label]=[focal] or [label]-", value))
         }
     }
 }
@@ -118,18 +117,16 @@ fn add_to_boxes(label: String, focal: u8, boxes: &mut Boxes) {
     lens_box.insert_or_swap(label, focal)
 }
 
-fn part2(input: &str) -> usize {
-    let lens_ops: Vec<Op> = input
-        .trim()
-        .split(',')
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .unwrap();
+pub fn part2(input: &str) -> usize {
+    Day15.part2(&Day15.parse(input).unwrap()).unwrap()
+}
+
+fn arrange_lenses(steps: &[Step]) -> usize {
     let mut boxes: [MapWithInsertionOrder; 256] =
         std::array::from_fn(|_| MapWithInsertionOrder::new());
 
-    for op in lens_ops {
-        match op {
+    for (_, op) in steps {
+        match op.clone() {
             Op::Eq(label, focal) => add_to_boxes(label, focal, &mut boxes),
             Op::Sub(label) => remove_from_boxes(label, &mut boxes),
         }
@@ -138,12 +135,28 @@ fn part2(input: &str) -> usize {
     focusing_power(&boxes).sum()
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(15)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input<'a> = Vec<Step<'a>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Vec<Step<'a>>, ParseError> {
+        input
+            .trim()
+            .split(',')
+            .map(|step| Ok((step, step.parse()?)))
+            .collect()
+    }
 
-    Ok(())
+    fn part1(&self, steps: &Vec<Step>) -> usize {
+        steps.iter().map(|(step, _)| capital_hash(step)).sum()
+    }
+
+    fn part2(&self, steps: &Vec<Step>) -> Option<usize> {
+        Some(arrange_lenses(steps))
+    }
 }
 
 #[test]
@@ -180,18 +193,10 @@ fn test_lens_op_parse() {
     assert_eq!("fszj-".parse::<Op>(), Ok(Op::Sub("fszj".into())));
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(15).unwrap();
-    assert_eq!(part1(input), 504036);
-    assert_eq!(part2(input), 295719);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(15).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(15, 1, part1(input));
+    assert_answer(15, 2, part2(input));
 }
@@ -1,156 +1,54 @@
-extern crate test;
+//! [Day 16: The Floor Will Be Lava](https://adventofcode.com/2023/day/16)
 
+use itertools::Itertools;
 use std::collections::{HashSet, VecDeque};
-use std::ops::{Add, AddAssign, Sub};
 
-use crate::day16::Direction::{Down, Left, Right, Up};
-use ndarray::{s, Array2, Dim};
-#[cfg(test)]
-use test::Bencher;
+use crate::parse::ParseError;
+use crate::solution::Solution;
+use crate::utils::grid::Direction::{self, Down, Left, Right, Up};
+use crate::utils::grid::{parse_grid, Grid, Position};
+use crate::utils::parallel::par_map;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
+use crate::visualize::{Rgb, Scene};
 
-use crate::utils::{parse_board, read_input_to_string};
+type Board = Grid<char>;
 
-type Board = Array2<char>;
-
-impl Direction {
-    fn opposite_direction(&self) -> Direction {
-        match self {
-            Up => Down,
-            Down => Up,
-            Left => Right,
-            Right => Left,
-        }
-    }
-
-    fn left_mirror(&self) -> Direction {
-        // This mirror:  \
-        match self {
-            Up => Right,
-            Left => Down,
-            Down => Left,
-            Right => Up,
-        }
-    }
-
-    fn right_mirror(&self) -> Direction {
-        // This mirror:  /
-        match self {
-            Up => Left,
-            Left => Up,
-            Down => Right,
-            Right => Down,
-        }
-    }
-
-    fn vertical_splitter(&self) -> Exit {
-        match self {
-            Up => Exit::Single(Down),
-            Left => Exit::Split(Up, Down),
-            Down => Exit::Single(Up),
-            Right => Exit::Split(Up, Down),
-        }
-    }
-
-    fn horizontal_splitter(&self) -> Exit {
-        match self {
-            Up => Exit::Split(Left, Right),
-            Left => Exit::Single(Right),
-            Down => Exit::Split(Left, Right),
-            Right => Exit::Single(Left),
-        }
-    }
-}
-
-fn pad(arr: &Board, value: char) -> Board {
-    // janky pad implementation
-    let mut board = Array2::from_elem(arr.raw_dim() + Dim([2, 2]), ' ');
-    board.fill(value);
-    board
-        .slice_mut(s![1..board.shape()[0] - 1, 1..board.shape()[1] - 1])
-        .assign(arr);
-    board
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Left,
-    Down,
-    Right,
-}
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Position {
-    x: usize,
-    y: usize,
-}
-
-impl Position {
-    fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
-    }
-
-    fn to_index(self) -> [usize; 2] {
-        [self.y, self.x]
-    }
-}
-
-impl Add<Direction> for Position {
-    type Output = Position;
-
-    fn add(self, dir: Direction) -> Position {
-        match dir {
-            Up => Position {
-                x: self.x,
-                y: self.y - 1,
-            },
-            Left => Position {
-                x: self.x - 1,
-                y: self.y,
-            },
-            Down => Position {
-                x: self.x,
-                y: self.y + 1,
-            },
-            Right => Position {
-                x: self.x + 1,
-                y: self.y,
-            },
-        }
+fn left_mirror(dir: Direction) -> Direction {
+    // This mirror:  \
+    match dir {
+        Up => Right,
+        Left => Down,
+        Down => Left,
+        Right => Up,
     }
 }
 
-impl AddAssign<Direction> for Position {
-    fn add_assign(&mut self, other: Direction) {
-        *self = *self + other;
+fn right_mirror(dir: Direction) -> Direction {
+    // This mirror:  /
+    match dir {
+        Up => Left,
+        Left => Up,
+        Down => Right,
+        Right => Down,
     }
 }
 
-impl Add for Position {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self::Output {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
-        }
+fn vertical_splitter(dir: Direction) -> Exit {
+    match dir {
+        Up => Exit::Single(Down),
+        Left => Exit::Split(Up, Down),
+        Down => Exit::Single(Up),
+        Right => Exit::Split(Up, Down),
     }
 }
 
-impl Sub for Position {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self::Output {
-        Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-        }
-    }
-}
-
-impl AddAssign for Position {
-    fn add_assign(&mut self, other: Self) {
-        *self = *self + other;
+fn horizontal_splitter(dir: Direction) -> Exit {
+    match dir {
+        Up => Exit::Split(Left, Right),
+        Left => Exit::Single(Right),
+        Down => Exit::Split(Left, Right),
+        Right => Exit::Single(Left),
     }
 }
 
@@ -163,17 +61,17 @@ enum Exit {
 
 fn beam_encounter(entry_dir: Direction, c: char) -> Exit {
     match c {
-        '.' => Exit::Single(entry_dir.opposite_direction()),
-        '/' => Exit::Single(entry_dir.right_mirror()),
-        '\\' => Exit::Single(entry_dir.left_mirror()),
-        '-' => entry_dir.horizontal_splitter(),
-        '|' => entry_dir.vertical_splitter(),
+        '.' => Exit::Single(entry_dir.opposite()),
+        '/' => Exit::Single(right_mirror(entry_dir)),
+        '\\' => Exit::Single(left_mirror(entry_dir)),
+        '-' => horizontal_splitter(entry_dir),
+        '|' => vertical_splitter(entry_dir),
         ' ' => Exit::OutOfBounds,
         _ => panic!("bad char in cell {c}"),
     }
 }
 
-type BeamExits = Array2<HashSet<Direction>>;
+type BeamExits = Grid<HashSet<Direction>>;
 
 fn maybe_push(
     pos: Position,
@@ -181,21 +79,21 @@ fn maybe_push(
     beam_exits: &mut BeamExits,
     beams: &mut VecDeque<(Position, Direction)>,
 ) {
-    let visited = &mut beam_exits[pos.to_index()];
+    let visited = &mut beam_exits[pos];
     if !visited.contains(&exit_dir) {
         visited.insert(exit_dir);
-        beams.push_back((pos + exit_dir, exit_dir.opposite_direction()))
+        beams.push_back((pos + exit_dir, exit_dir.opposite()))
     }
 }
 
-fn beaming_to_energized(start: (Position, Direction), board: &Board) -> usize {
-    let mut beam_exits: BeamExits = Array2::from_elem(board.raw_dim(), HashSet::new());
+fn energized_cells(start: (Position, Direction), board: &Board) -> Vec<Position> {
+    let mut beam_exits: BeamExits = board.map(|_| HashSet::new());
     let mut beams: VecDeque<(Position, Direction)> = VecDeque::new();
 
     beams.push_back(start);
 
     while let Some((pos, dir)) = beams.pop_front() {
-        let exit = beam_encounter(dir, board[pos.to_index()]);
+        let exit = beam_encounter(dir, board[pos]);
 
         match exit {
             Exit::Split(a, b) => {
@@ -207,63 +105,103 @@ fn beaming_to_energized(start: (Position, Direction), board: &Board) -> usize {
         }
     }
 
-    beam_exits.iter().filter(|e| !e.is_empty()).count()
+    beam_exits
+        .iter()
+        .filter(|(_, e)| !e.is_empty())
+        .map(|(pos, _)| pos)
+        .collect()
+}
+
+fn beaming_to_energized(start: (Position, Direction), board: &Board) -> usize {
+    energized_cells(start, board).len()
 }
 
-fn part1(input: &str) -> usize {
-    let board = parse_board(input);
-    let board = pad(&board, ' ');
+fn energized_from_corner(board: &Board) -> usize {
+    let board = board.padded(1, ' ');
     beaming_to_energized((Position::new(1, 1), Left), &board)
 }
 
 fn edge_positions(board: &Board) -> impl Iterator<Item = Position> + '_ {
-    (0..board.dim().0)
+    let corner = board.bottom_right();
+    (0..=corner.y)
         .map(|v| Position::new(0, v))
-        .chain((0..board.dim().0).map(|v| Position::new(board.dim().1 - 1, v)))
-        .chain((0..board.dim().1).map(|v| Position::new(v, 0)))
-        .chain((0..board.dim().1).map(|v| Position::new(v, board.dim().0 - 1)))
+        .chain((0..=corner.y).map(move |v| Position::new(corner.x, v)))
+        .chain((0..=corner.x).map(|v| Position::new(v, 0)))
+        .chain((0..=corner.x).map(move |v| Position::new(v, corner.y)))
 }
 
 fn start_directions(start_pos: Position, board: &Board) -> Vec<Direction> {
     let mut out = Vec::new();
 
+    let corner = board.bottom_right();
     if start_pos.x == 1 {
         out.push(Left);
-    } else if start_pos.x == board.dim().1 - 2 {
+    } else if start_pos.x == corner.x - 1 {
         out.push(Right);
     }
 
     if start_pos.y == 1 {
         out.push(Up);
-    } else if start_pos.y == board.dim().0 - 2 {
+    } else if start_pos.y == corner.y - 1 {
         out.push(Down);
     }
 
     out
 }
 
-fn part2(input: &str) -> usize {
-    let board = parse_board(input);
-    let edge_pos_iter = edge_positions(&board);
+fn most_energized(board: &Board) -> usize {
+    let padded = board.padded(1, ' ');
+    let starts = edge_positions(board)
+        .map(|pos| pos + Position::new(1, 1))
+        .flat_map(|pos| {
+            start_directions(pos, &padded)
+                .into_iter()
+                .map(move |dir| (pos, dir))
+        })
+        .collect_vec();
+
+    par_map(&starts, |start| beaming_to_energized(*start, &padded))
+        .into_iter()
+        .max()
+        .unwrap()
+}
 
-    let board = pad(&board, ' ');
-    let mut energized: Vec<(Position, usize)> = Vec::new();
+pub struct Day16;
 
-    for pos in edge_pos_iter.map(|pos| pos + Position::new(1, 1)) {
-        for dir in start_directions(pos, &board) {
-            energized.push((pos, beaming_to_energized((pos, dir), &board)));
-        }
+impl Solution for Day16 {
+    type Input<'a> = Board;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(&self, input: &str) -> Result<Board, ParseError> {
+        Ok(parse_grid(input, |c| "./\\-|".contains(c).then_some(c))?)
+    }
+
+    fn part1(&self, board: &Board) -> usize {
+        energized_from_corner(board)
     }
 
-    *energized.iter().map(|(_, v)| v).max().unwrap()
+    fn part2(&self, board: &Board) -> Option<usize> {
+        Some(most_energized(board))
+    }
+
+    fn scene(&self, board: &Board) -> Option<Scene> {
+        let energized = energized_cells((Position::new(1, 1), Left), &board.padded(1, ' '));
+        Some(Scene::new(board.cells().clone()).layer(
+            "energized from the corner",
+            Rgb::ORANGE,
+            None,
+            energized.into_iter().map(|pos| pos - Position::new(1, 1)),
+        ))
+    }
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(16)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub fn part1(input: &str) -> usize {
+    Day16.part1(&Day16.parse(input).unwrap())
+}
 
-    Ok(())
+pub fn part2(input: &str) -> usize {
+    Day16.part2(&Day16.parse(input).unwrap()).unwrap()
 }
 
 #[test]
@@ -282,18 +220,10 @@ fn example() {
     assert_eq!(part2(input), 51);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(16).unwrap();
-    assert_eq!(part1(input), 6883);
-    assert_eq!(part2(input), 7228);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(16).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(16, 1, part1(input));
+    assert_answer(16, 2, part2(input));
 }
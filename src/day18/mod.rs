@@ -1,169 +1,97 @@
-extern crate test;
+//! [Day 18: Lavaduct Lagoon](https://adventofcode.com/2023/day/18)
 
-use crate::day18::Direction::{Down, Left, Right, Up};
 use itertools::Itertools;
-use std::fmt;
-use std::ops::{Add, AddAssign};
-use std::str::FromStr;
 
-#[cfg(test)]
-use test::Bencher;
-
-use crate::utils::read_input_to_string;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Left,
-    Down,
-    Right,
+use crate::parse::{number, parse_lines, ParseError, ParseResult};
+use crate::solution::Solution;
+use crate::utils::grid::Direction::{self, Down, Left, Right, Up};
+use crate::utils::grid::Position;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
+
+type Step = (Direction, usize);
+
+fn direction_from_digit(digit: char) -> ParseResult<Direction> {
+    match digit {
+        '0' => Ok(Right),
+        '1' => Ok(Down),
+        '2' => Ok(Left),
+        '3' => Ok(Up),
+        _ => Err(ParseError::unknown("direction digit", digit)),
+    }
 }
 
-impl Direction {
-    fn from_digit(digit: u32) -> Self {
-        match digit {
-            0 => Right,
-            1 => Down,
-            2 => Left,
-            3 => Up,
-            _ => panic!("Bad direction digit {digit}"),
-        }
+fn parse_direction(s: &str) -> ParseResult<Direction> {
+    match s {
+        "U" => Ok(Up),
+        "L" => Ok(Left),
+        "D" => Ok(Down),
+        "R" => Ok(Right),
+        _ => Err(ParseError::unknown("direction", s)),
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Position {
-    x: isize,
-    y: isize,
+/// The step as written and the one hidden in the colour, for part 1 and 2
+fn parse_step(input: &str) -> ParseResult<(Step, Step)> {
+    let Some((dir, n, hexa)) = input.split_whitespace().collect_tuple() else {
+        return Err(ParseError::format("[direction] [meters] (#[hexa])", input));
+    };
+    let hexa = hexa
+        .strip_prefix("(#")
+        .and_then(|hexa| hexa.strip_suffix(')'))
+        .filter(|hexa| hexa.len() == 6 && hexa.is_char_boundary(5))
+        .ok_or_else(|| ParseError::format("(#[6 hexa digits])", hexa))?;
+    let (meters, direction) = hexa.split_at(5);
+    let meters = usize::from_str_radix(meters, 16).map_err(|_| ParseError::number(meters))?;
+    let direction = direction_from_digit(direction.chars().next().unwrap())?;
+    Ok(((parse_direction(dir)?, number(n)?), (direction, meters)))
 }
 
-impl Position {
-    fn new(x: isize, y: isize) -> Self {
-        Self { x, y }
-    }
+fn shoelace(start_pos: Position, edges: &[(Direction, usize)]) -> u64 {
+    let mut area: i64 = 2;
 
-    fn move_n_steps(self, dir: Direction, n: usize) -> Self {
-        let mut pos = self;
-        for _ in 0..n {
-            pos += dir;
-        }
-        pos
-    }
-}
+    let mut current_position = start_pos;
+    for (dir, n) in edges.iter() {
+        let next_pos = current_position.moved(*dir, *n as isize);
 
-impl Add<Direction> for Position {
-    type Output = Position;
-
-    fn add(self, dir: Direction) -> Position {
-        match dir {
-            Up => Position {
-                x: self.x,
-                y: self.y - 1,
-            },
-            Left => Position {
-                x: self.x - 1,
-                y: self.y,
-            },
-            Down => Position {
-                x: self.x,
-                y: self.y + 1,
-            },
-            Right => Position {
-                x: self.x + 1,
-                y: self.y,
-            },
-        }
+        area += current_position.x as i64 * next_pos.y as i64;
+        area -= next_pos.x as i64 * current_position.y as i64;
+        area += *n as i64;
+        current_position = next_pos;
     }
-}
 
-impl AddAssign<Direction> for Position {
-    fn add_assign(&mut self, other: Direction) {
-        *self = *self + other;
-    }
+    (area / 2) as u64
 }
 
-impl FromStr for Direction {
-    type Err = ParseDirectionError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "U" => Ok(Up),
-            "L" => Ok(Left),
-            "D" => Ok(Down),
-            "R" => Ok(Right),
-            _ => Err(ParseDirectionError(s.to_string())),
-        }
-    }
+pub fn part1(input: &str) -> u64 {
+    Day18.part1(&Day18.parse(input).unwrap())
 }
 
-#[derive(Debug, Clone)]
-struct ParseDirectionError(String);
-
-impl fmt::Display for ParseDirectionError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "invalid direction: {}", self.0)
-    }
+pub fn part2(input: &str) -> u64 {
+    Day18.part2(&Day18.parse(input).unwrap()).unwrap()
 }
 
-impl std::error::Error for ParseDirectionError {}
+pub struct Day18;
 
-fn parse_step_p1(input: &str) -> (Direction, usize) {
-    if let Some((dir, n, _)) = input.split_whitespace().collect_tuple() {
-        (dir.parse().unwrap(), n.parse().unwrap())
-    } else {
-        panic!("bad step {input}")
-    }
-}
+impl Solution for Day18 {
+    /// The plan with the steps as written and as hidden in the colours
+    type Input<'a> = Vec<(Step, Step)>;
+    type Answer1 = u64;
+    type Answer2 = u64;
 
-fn parse_step_p2(input: &str) -> (Direction, usize) {
-    if let Some((_, _, hexa)) = input.split_whitespace().collect_tuple() {
-        let number = &hexa[2..7];
-        let direction = hexa.chars().nth(7).unwrap();
-        (
-            Direction::from_digit(
-                direction
-                    .to_digit(10)
-                    .unwrap_or_else(|| panic!("bad direction {direction}")),
-            ),
-            usize::from_str_radix(number, 16).unwrap_or_else(|_| panic!("Bad hexa {number}")),
-        )
-    } else {
-        panic!("bad step {input}")
+    fn parse(&self, input: &str) -> Result<Vec<(Step, Step)>, ParseError> {
+        parse_lines(input.trim(), parse_step)
     }
-}
-
-fn shoelace(start_pos: Position, edges: &[(Direction, usize)]) -> usize {
-    let mut area: isize = 2;
-
-    let mut current_position = start_pos;
-    for (dir, n) in edges.iter() {
-        let next_pos = current_position.move_n_steps(*dir, *n);
 
-        area += current_position.x * next_pos.y;
-        area -= next_pos.x * current_position.y;
-        area += *n as isize;
-        current_position = next_pos;
+    fn part1(&self, plan: &Vec<(Step, Step)>) -> u64 {
+        let moves = plan.iter().map(|(step, _)| *step).collect_vec();
+        shoelace(Position::ORIGIN, &moves)
     }
 
-    (area / 2) as usize
-}
-
-fn part1(input: &str) -> usize {
-    let moves = input.trim().split('\n').map(parse_step_p1).collect_vec();
-    shoelace(Position::new(0, 0), &moves)
-}
-
-fn part2(input: &str) -> usize {
-    let moves = input.trim().split('\n').map(parse_step_p2).collect_vec();
-    shoelace(Position::new(0, 0), &moves)
-}
-
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(18)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
-
-    Ok(())
+    fn part2(&self, plan: &Vec<(Step, Step)>) -> Option<u64> {
+        let moves = plan.iter().map(|(_, step)| *step).collect_vec();
+        Some(shoelace(Position::ORIGIN, &moves))
+    }
 }
 
 #[test]
@@ -186,18 +114,10 @@ U 2 (#7a21e3)";
     assert_eq!(part2(input), 952408144115);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(18).unwrap();
-    assert_eq!(part1(input), 62500);
-    assert_eq!(part2(input), 122109860712709);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(18).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(18, 1, part1(input));
+    assert_answer(18, 2, part2(input));
 }
@@ -1,34 +1,20 @@
-extern crate test;
+//! [Day 19: Aplenty](https://adventofcode.com/2023/day/19)
 
 use itertools::Itertools;
 use std::collections::{HashMap, VecDeque};
-use std::error::Error;
 use std::fmt;
 use std::ops::RangeInclusive;
 
 use petgraph::algo::is_cyclic_directed;
+use petgraph::dot::Dot;
 use petgraph::graph::{DiGraph, NodeIndex};
-#[cfg(test)]
-use test::Bencher;
 
-use crate::utils::read_input_to_string;
-use std::num::ParseIntError;
+use crate::parse::{number, parse_lines, split_block, split_once, ParseError, ParseResult};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 use std::str::FromStr;
 
-#[derive(Debug)]
-enum ParseConditionError {
-    TooShort,
-    InvalidFormat,
-    InvalidNumber,
-}
-
-#[derive(Debug)]
-enum ParsePartError {
-    MissingField(String),
-    ParseIntError(String, ParseIntError),
-    InvalidFormat(String),
-}
-
 impl fmt::Display for Destination {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -39,18 +25,6 @@ impl fmt::Display for Destination {
     }
 }
 
-impl fmt::Display for ParseDestinationError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Invalid format")
-    }
-}
-
-impl From<(String, ParseIntError)> for ParsePartError {
-    fn from(err: (String, ParseIntError)) -> Self {
-        ParsePartError::ParseIntError(err.0, err.1)
-    }
-}
-
 impl fmt::Display for Op {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -60,29 +34,23 @@ impl fmt::Display for Op {
     }
 }
 
-#[derive(Debug)]
-enum ParseDestinationError {
-    InvalidFormat,
-}
-
-impl Error for ParseDestinationError {}
-
+/// A machine part with its four ratings, parsed from `{x=787,m=2655,a=1222,s=2876}`
 #[derive(Debug, Clone)]
-struct Part {
-    x: usize,
-    m: usize,
-    a: usize,
-    s: usize,
+pub struct Part {
+    pub x: usize,
+    pub m: usize,
+    pub a: usize,
+    pub s: usize,
 }
 
 impl Part {
-    fn sum(&self) -> usize {
+    pub fn sum(&self) -> usize {
         self.x + self.m + self.a + self.s
     }
 }
 
 impl FromStr for Part {
-    type Err = ParsePartError;
+    type Err = ParseError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         let mut x = None;
@@ -90,49 +58,45 @@ impl FromStr for Part {
         let mut a = None;
         let mut s = None;
 
-        for pair in input.trim_matches(|c| c == '{' || c == '}').split(',') {
-            let mut split = pair.split('=');
-            match (split.next(), split.next()) {
-                (Some("x"), Some(value)) => {
-                    x = Some(value.parse().map_err(|e| ("x".to_string(), e))?)
-                }
-                (Some("m"), Some(value)) => {
-                    m = Some(value.parse().map_err(|e| ("m".to_string(), e))?)
-                }
-                (Some("a"), Some(value)) => {
-                    a = Some(value.parse().map_err(|e| ("a".to_string(), e))?)
-                }
-                (Some("s"), Some(value)) => {
-                    s = Some(value.parse().map_err(|e| ("s".to_string(), e))?)
-                }
-                (Some(field), _) => return Err(ParsePartError::InvalidFormat(field.to_string())),
-                _ => return Err(ParsePartError::InvalidFormat("Unknown format".to_string())),
+        let ratings = input
+            .strip_prefix('{')
+            .and_then(|ratings| ratings.strip_suffix('}'))
+            .ok_or_else(|| ParseError::format("{[ratings]}", input))?;
+        for pair in ratings.split(',') {
+            let (rating, value) = split_once(pair, "=", "[rating]=[value]")?;
+            let value = Some(number(value)?);
+            match rating {
+                "x" => x = value,
+                "m" => m = value,
+                "a" => a = value,
+                "s" => s = value,
+                _ => return Err(ParseError::unknown("rating", rating)),
             }
         }
 
         Ok(Part {
-            x: x.ok_or_else(|| ParsePartError::MissingField("x".to_string()))?,
-            m: m.ok_or_else(|| ParsePartError::MissingField("m".to_string()))?,
-            a: a.ok_or_else(|| ParsePartError::MissingField("a".to_string()))?,
-            s: s.ok_or_else(|| ParsePartError::MissingField("s".to_string()))?,
+            x: x.ok_or_else(|| ParseError::missing("rating x"))?,
+            m: m.ok_or_else(|| ParseError::missing("rating m"))?,
+            a: a.ok_or_else(|| ParseError::missing("rating a"))?,
+            s: s.ok_or_else(|| ParseError::missing("rating s"))?,
         })
     }
 }
 
 #[derive(Debug, Copy, Clone)]
-enum Op {
+pub enum Op {
     Greater,
     Less,
 }
 
 impl FromStr for Op {
-    type Err = Box<dyn Error>;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             ">" => Ok(Op::Greater),
             "<" => Ok(Op::Less),
-            _ => Err("Invalid operator".into()),
+            _ => Err(ParseError::unknown("operator", s)),
         }
     }
 }
@@ -147,35 +111,29 @@ impl Op {
     }
 }
 
+/// A rule's comparison of one rating against a value, like `a<2006`
 #[derive(Debug, Clone)]
-struct Condition {
-    variable: char,
-    condition: Op,
-    value: usize,
+pub struct Condition {
+    pub variable: char,
+    pub condition: Op,
+    pub value: usize,
 }
 
 impl FromStr for Condition {
-    type Err = ParseConditionError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let bytes = s.as_bytes();
-
-        if bytes.len() < 3 {
-            return Err(ParseConditionError::TooShort);
-        }
-
-        let variable = bytes[0] as char;
-        let condition = bytes[1] as char;
+        let mut chars = s.chars();
+        let (Some(variable), Some(condition)) = (chars.next(), chars.next()) else {
+            return Err(ParseError::format("[rating][operator][value]", s));
+        };
 
-        if !variable.is_alphabetic() || !(condition == '>' || condition == '<') {
-            return Err(ParseConditionError::InvalidFormat);
+        if !"xmas".contains(variable) {
+            return Err(ParseError::unknown("rating", variable));
         }
-
-        let condition = Op::from_char(condition).unwrap();
-        let value_str = &s[2..];
-        let value = value_str
-            .parse()
-            .map_err(|_| ParseConditionError::InvalidNumber)?;
+        let condition =
+            Op::from_char(condition).ok_or_else(|| ParseError::unknown("operator", condition))?;
+        let value = number(chars.as_str())?;
 
         Ok(Condition {
             variable,
@@ -186,7 +144,7 @@ impl FromStr for Condition {
 }
 
 impl Condition {
-    fn check(&self, part: &Part) -> bool {
+    pub fn check(&self, part: &Part) -> bool {
         let part_value = match self.variable {
             'x' => part.x,
             'm' => part.m,
@@ -217,8 +175,9 @@ impl Condition {
     }
 }
 
-#[derive(Hash, Clone, Eq, PartialEq)]
-enum Destination {
+/// Where a rule sends a part: another workflow by name, or accepted (`A`) or rejected (`R`)
+#[derive(Debug, Hash, Clone, Eq, PartialEq)]
+pub enum Destination {
     Workflow(String),
     Terminal(bool),
 }
@@ -230,51 +189,70 @@ impl Destination {
 }
 
 impl FromStr for Destination {
-    type Err = ParseDestinationError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "A" => Ok(Destination::Terminal(true)),
             "R" => Ok(Destination::Terminal(false)),
-            _ if s.chars().all(|c| c.is_ascii_lowercase()) => {
+            _ if !s.is_empty() && s.chars().all(|c| c.is_ascii_lowercase()) => {
                 Ok(Destination::Workflow(s.to_string()))
             }
-            _ => Err(ParseDestinationError::InvalidFormat),
+            _ => Err(ParseError::format("A, R or a workflow name", s)),
         }
     }
 }
 
-fn parse_condition(input: &str) -> (Condition, String) {
-    if let Some((condition, destination)) = input.split(':').collect_tuple() {
-        (condition.parse().unwrap(), destination.to_owned())
-    } else {
-        panic!("bad workflow {input}")
-    }
+fn parse_condition(input: &str) -> ParseResult<(Condition, Destination)> {
+    let (condition, destination) = split_once(input, ":", "[condition]:[destination]")?;
+    Ok((condition.parse()?, destination.parse()?))
 }
 
-type WorkflowItem = (Option<Condition>, Destination);
-type WorkflowMap = HashMap<Destination, Vec<WorkflowItem>>;
+/// A rule of a workflow, the last one of each workflow has no condition
+pub type WorkflowItem = (Option<Condition>, Destination);
+pub type WorkflowMap = HashMap<Destination, Vec<WorkflowItem>>;
+
+/// Parses a workflow like `px{a<2006:qkq,m>2090:A,rfg}` into its name and rules
+pub fn parse_workflow(input: &str) -> ParseResult<(Destination, Vec<WorkflowItem>)> {
+    let (name, workflow_str) = split_once(input, "{", "[name]{[rules]}")?;
+    let workflow_str = workflow_str
+        .strip_suffix('}')
+        .ok_or_else(|| ParseError::format("[name]{[rules]}", input))?;
+    let workflow_str = workflow_str.split(',').collect_vec();
+    let (fallback, rules) = workflow_str.split_last().unwrap();
+    let mut workflow = rules
+        .iter()
+        .map(|s| {
+            let (condition, destination) = parse_condition(s)?;
+            Ok((Some(condition), destination))
+        })
+        .collect::<ParseResult<Vec<_>>>()?;
+    workflow.push((None, fallback.parse()?));
 
-fn parse_workflow(input: &str) -> (Destination, Vec<WorkflowItem>) {
-    if let Some((name, workflow_str)) = input.split('{').collect_tuple() {
-        let workflow_str = &workflow_str[..workflow_str.len() - 1];
-        let workflow_str = workflow_str.split(',').collect_vec();
-        let mut workflow = workflow_str[..workflow_str.len() - 1]
-            .iter()
-            .map(|s| {
-                let (condition, destination) = parse_condition(s);
-                (Some(condition), destination.parse().unwrap())
-            })
-            .collect_vec();
-        workflow.push((None, workflow_str.last().unwrap().parse().unwrap()));
+    Ok((name.parse()?, workflow))
+}
 
-        (name.parse().unwrap(), workflow)
-    } else {
-        panic!("bad workflow {input}")
+/// Parses the workflows and checks that `in` and every workflow they send parts to exist
+fn parse_workflows(input: &str) -> ParseResult<WorkflowMap> {
+    let workflows = parse_lines(input, parse_workflow)?;
+    let names: Vec<&Destination> = workflows.iter().map(|(name, _)| name).collect();
+    if !names.contains(&&Destination::start()) {
+        return Err(ParseError::missing("workflow 'in'"));
     }
+    for (i, (_, workflow)) in workflows.iter().enumerate() {
+        for (_, destination) in workflow {
+            if let Destination::Workflow(name) = destination {
+                if !names.contains(&destination) {
+                    return Err(ParseError::unknown("workflow", name).at_line(i + 1));
+                }
+            }
+        }
+    }
+    Ok(workflows.into_iter().collect())
 }
 
-fn check_part(part: &Part, workflows: &WorkflowMap) -> bool {
+/// Runs a part through the workflows starting at `in`, true if it ends up accepted
+pub fn check_part(part: &Part, workflows: &WorkflowMap) -> bool {
     let mut destination = Destination::start();
 
     while let Destination::Workflow(_) = destination {
@@ -299,23 +277,12 @@ fn check_part(part: &Part, workflows: &WorkflowMap) -> bool {
     }
 }
 
-fn part1(input: &str) -> usize {
-    let mut lines_iter = input.lines();
-
-    let workflows: WorkflowMap = lines_iter
-        .by_ref()
-        .take_while(|line| !line.is_empty())
-        .map(parse_workflow)
-        .collect();
-    let parts: Vec<Part> = lines_iter
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .unwrap();
-
+/// Sum of the ratings of all accepted parts
+pub fn accepted_ratings(workflows: &WorkflowMap, parts: &[Part]) -> usize {
     parts
         .iter()
         .filter_map(|part| {
-            if check_part(part, &workflows) {
+            if check_part(part, workflows) {
                 Some(part.sum())
             } else {
                 None
@@ -330,6 +297,20 @@ enum Node {
     Exit(bool),
 }
 
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Cond(condition) => write!(
+                f,
+                "{} {} {}",
+                condition.variable, condition.condition, condition.value
+            ),
+            Node::Exit(true) => write!(f, "A"),
+            Node::Exit(false) => write!(f, "R"),
+        }
+    }
+}
+
 impl fmt::Debug for Node {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -378,11 +359,11 @@ impl ValidRatings {
         }
     }
 
-    fn count(&self) -> usize {
+    fn count(&self) -> u64 {
         if let Some(range) = self.range.as_ref() {
             let (start, end) = (range.start(), range.end());
             if start <= end {
-                end - start + 1
+                (end - start + 1) as u64
             } else {
                 0
             }
@@ -424,14 +405,9 @@ fn dfs(graph: &DiGraph<Node, bool>, current: NodeIndex, end: NodeIndex) -> Vec<V
 
 type OutgoingNode = Option<(NodeIndex<u32>, bool)>;
 
-fn part2(input: &str) -> usize {
-    let workflows: WorkflowMap = input
-        .lines()
-        .by_ref()
-        .take_while(|line| !line.is_empty())
-        .map(parse_workflow)
-        .collect();
-
+/// The workflows as a tree of conditions starting at `in`, with edges for whether the condition
+/// held and the accept and reject exits as the first two nodes
+fn workflow_graph(workflows: &WorkflowMap) -> (DiGraph<Node, bool>, NodeIndex) {
     let mut g = DiGraph::new();
 
     let mut queue: VecDeque<(OutgoingNode, Destination)> = VecDeque::new();
@@ -491,6 +467,12 @@ fn part2(input: &str) -> usize {
     }
 
     assert!(!is_cyclic_directed(&g));
+    (g, accept_idx)
+}
+
+/// How many of the 4000^4 possible rating combinations the workflows accept
+pub fn accepted_combinations(workflows: &WorkflowMap) -> u64 {
+    let (g, accept_idx) = workflow_graph(workflows);
 
     let paths = find_paths(&g, 2u32.into(), accept_idx);
 
@@ -527,18 +509,54 @@ fn part2(input: &str) -> usize {
                 }
             }
         }
-        valid_ratings += rating_ranges.map(|v| v.count()).iter().product::<usize>();
+        valid_ratings += rating_ranges.map(|v| v.count()).iter().product::<u64>();
     }
 
     valid_ratings
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(19)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+#[derive(Debug)]
+pub struct System {
+    pub workflows: WorkflowMap,
+    pub parts: Vec<Part>,
+}
+
+pub struct Day19;
 
-    Ok(())
+impl Solution for Day19 {
+    type Input<'a> = System;
+    type Answer1 = usize;
+    type Answer2 = u64;
+
+    fn parse(&self, input: &str) -> Result<System, ParseError> {
+        let (workflows, parts) = split_block(input).ok_or_else(|| ParseError::missing("parts"))?;
+        Ok(System {
+            workflows: parse_workflows(workflows)?,
+            parts: parse_lines(parts, str::parse)
+                .map_err(|err| err.offset(workflows.lines().count() + 1))?,
+        })
+    }
+
+    fn part1(&self, system: &System) -> usize {
+        accepted_ratings(&system.workflows, &system.parts)
+    }
+
+    fn part2(&self, system: &System) -> Option<u64> {
+        Some(accepted_combinations(&system.workflows))
+    }
+
+    fn dot(&self, system: &System) -> Option<String> {
+        let (g, _) = workflow_graph(&system.workflows);
+        Some(Dot::new(&g).to_string())
+    }
+}
+
+pub fn part1(input: &str) -> usize {
+    Day19.part1(&Day19.parse(input).unwrap())
+}
+
+pub fn part2(input: &str) -> u64 {
+    Day19.part2(&Day19.parse(input).unwrap()).unwrap()
 }
 
 #[test]
@@ -562,20 +580,19 @@ hdj{m>838:A,pv}
 {x=2127,m=1623,a=2188,s=1013}";
     assert_eq!(part1(input), 19114);
     assert_eq!(part2(input), 167409079868000);
+
+    let err = Day19
+        .parse(&input.replace("m>838:A", "m>838:xyz"))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "line 11: unknown workflow 'xyz'");
+    let err = Day19.parse(&input.replace("a=466", "a=46x")).unwrap_err();
+    assert_eq!(err.to_string(), "line 16: invalid number '46x'");
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(19).unwrap();
-    assert_eq!(part1(input), 325952);
-    assert_eq!(part2(input), 125744206494820);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(19).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(19, 1, part1(input));
+    assert_answer(19, 2, part2(input));
 }
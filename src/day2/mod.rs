@@ -1,91 +1,107 @@
-extern crate test;
+//! [Day 2: Cube Conundrum](https://adventofcode.com/2023/day/2)
 
-use itertools::Itertools;
 use std::collections::HashMap;
 
-#[cfg(test)]
-use test::Bencher;
+use crate::parse::{number, parse_lines, split_once, ParseError, ParseResult};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
-use crate::utils::read_input_to_string;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Red,
+    Green,
+    Blue,
+}
 
-fn parse_game_id(input: &str) -> u32 {
-    if let Some((_, game_id)) = input.split_whitespace().collect_tuple() {
-        game_id
-            .parse()
-            .unwrap_or_else(|_| panic!("Unexpected game id {game_id}"))
-    } else {
-        panic!("Expected format Game [id], got {input}")
+impl Color {
+    fn limit(self) -> u32 {
+        match self {
+            Color::Red => 12,
+            Color::Green => 13,
+            Color::Blue => 14,
+        }
     }
 }
 
-fn block_limit(block: &str) -> u32 {
-    match block {
-        "red" => 12,
-        "green" => 13,
-        "blue" => 14,
-        _ => panic!("Unexpected block {block}"),
-    }
+/// A game with every `[amount] [color]` drawn in it, the rounds don't matter for either part
+#[derive(Debug, Clone)]
+pub struct Game {
+    pub id: u32,
+    pub draws: Vec<(u32, Color)>,
 }
 
-fn block_over_limit(input: &str) -> bool {
-    if let Some((amount, color)) = input.split_whitespace().collect_tuple() {
-        amount.parse::<u32>().unwrap() > block_limit(color)
-    } else {
-        panic!("Expected format amount color, got {input}")
-    }
+fn parse_draw(input: &str) -> ParseResult<(u32, Color)> {
+    let (amount, color) = split_once(input.trim(), " ", "[amount] [color]")?;
+    let color = match color {
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "blue" => Color::Blue,
+        _ => return Err(ParseError::unknown("color", color)),
+    };
+    Ok((number(amount)?, color))
 }
 
-fn part1(input: &str) -> u32 {
-    let mut acc = 0;
-    for line in input.lines() {
-        if let Some((game, record)) = line.split(": ").collect_tuple() {
-            if !record
-                .split("; ")
-                .flat_map(|s| s.split(", "))
-                .map(block_over_limit)
-                .any(|value| value)
-            {
-                acc += parse_game_id(game);
-            }
-        }
-    }
-    acc
+fn parse_game(line: &str) -> ParseResult<Game> {
+    let (game, record) = split_once(line, ": ", "Game [id]: [draws]")?;
+    let id = game
+        .strip_prefix("Game ")
+        .ok_or_else(|| ParseError::format("Game [id]", game))?;
+    Ok(Game {
+        id: number(id)?,
+        draws: record
+            .split(&[';', ','])
+            .map(parse_draw)
+            .collect::<ParseResult<_>>()?,
+    })
 }
 
-type CubeCount = HashMap<String, u32>;
-
-fn update_cube_count(input: &str, cubes_needed: &mut CubeCount) {
-    if let Some((amount, color)) = input.split_whitespace().collect_tuple() {
-        let amount = amount.parse().unwrap();
-        cubes_needed
-            .entry(color.to_string())
-            .and_modify(|e| *e = (*e).max(amount))
-            .or_insert(amount);
-    } else {
-        panic!("Expected format `[amount] [color]`, got {input}")
-    }
+pub fn part1(input: &str) -> u32 {
+    Day2.part1(&Day2.parse(input).unwrap())
 }
 
-fn part2(input: &str) -> u32 {
-    let mut acc = 0;
-    for line in input.lines() {
-        let mut blocks: HashMap<String, u32> = HashMap::new();
-        if let Some((_, record)) = line.split(": ").collect_tuple() {
-            for cube_draw in record.split("; ").flat_map(|s| s.split(", ")) {
-                update_cube_count(cube_draw, &mut blocks)
-            }
-        }
-        acc += blocks.values().product::<u32>()
-    }
-    acc
+pub fn part2(input: &str) -> u32 {
+    Day2.part2(&Day2.parse(input).unwrap()).unwrap()
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(2)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub struct Day2;
 
-    Ok(())
+impl Solution for Day2 {
+    type Input<'a> = Vec<Game>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, input: &str) -> Result<Vec<Game>, ParseError> {
+        parse_lines(input, parse_game)
+    }
+
+    fn part1(&self, games: &Vec<Game>) -> u32 {
+        games
+            .iter()
+            .filter(|game| {
+                game.draws
+                    .iter()
+                    .all(|(amount, color)| *amount <= color.limit())
+            })
+            .map(|game| game.id)
+            .sum()
+    }
+
+    fn part2(&self, games: &Vec<Game>) -> Option<u32> {
+        Some(
+            games
+                .iter()
+                .map(|game| {
+                    let mut cubes_needed = HashMap::new();
+                    for &(amount, color) in &game.draws {
+                        let needed = cubes_needed.entry(color).or_insert(amount);
+                        *needed = (*needed).max(amount);
+                    }
+                    cubes_needed.values().product::<u32>()
+                })
+                .sum(),
+        )
+    }
 }
 
 #[test]
@@ -97,20 +113,17 @@ Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
     assert_eq!(part1(input), 8);
     assert_eq!(part2(input), 2286);
+
+    let err = Day2
+        .parse("Game 1: 3 blue\nGame 2: 1 purple, 2 red")
+        .unwrap_err();
+    assert_eq!(err.to_string(), "line 2: unknown color 'purple'");
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(2).unwrap();
-    assert_eq!(part1(input), 2545);
-    assert_eq!(part2(input), 78111);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(2).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(2, 1, part1(input));
+    assert_answer(2, 2, part2(input));
 }
@@ -1,58 +1,49 @@
-extern crate test;
+//! [Day 20: Pulse Propagation](https://adventofcode.com/2023/day/20)
 
 use itertools::Itertools;
+use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, Write};
 use std::iter::once;
+use std::rc::Rc;
 use std::str::FromStr;
 
-use num_integer::gcd;
+use num_integer::{ExtendedGcd, Integer};
+use petgraph::dot::{Config, Dot};
 use petgraph::graph::{DiGraph, NodeIndex};
-#[cfg(test)]
-use test::Bencher;
+use petgraph::Direction;
 
-use crate::utils::read_input_to_string;
-
-#[derive(Debug)]
-enum ParseModuleError {
-    InvalidFormat(String),
-}
-
-impl fmt::Display for ParseModuleError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            ParseModuleError::InvalidFormat(input) => write!(f, "Invalid module format: {}", input),
-        }
-    }
-}
+use crate::generate::Rng;
+use crate::parse::{parse_lines, split_once, ParseError};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 impl FromStr for Module {
-    type Err = ParseModuleError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(" -> ").collect();
-        if parts.len() != 2 {
-            return Err(ParseModuleError::InvalidFormat(s.to_string()));
-        }
+        let (label, destinations) = split_once(s, " -> ", "[module] -> [destinations]")?;
+        let destinations: Vec<String> = destinations.split(", ").map(String::from).collect();
 
-        let label = parts[0].to_string();
-        let destinations: Vec<String> = parts[1].split(", ").map(String::from).collect();
-
-        match label.chars().next() {
-            Some('&') => Ok(Module::C(Conjunction::new(
-                label[1..].to_string(),
-                destinations,
-            ))),
-            Some('%') => Ok(Module::F(FlipFlop::new(
-                label[1..].to_string(),
-                destinations,
-            ))),
-            _ => Ok(Module::B(Broadcaster::new(label, destinations))),
+        match label.split_at_checked(1) {
+            Some(("&", name)) if !name.is_empty() => {
+                Ok(Module::C(Conjunction::new(name.to_string(), destinations)))
+            }
+            Some(("%", name)) if !name.is_empty() => {
+                Ok(Module::F(FlipFlop::new(name.to_string(), destinations)))
+            }
+            _ if label == "broadcaster" => {
+                Ok(Module::B(Broadcaster::new(label.to_string(), destinations)))
+            }
+            _ => Err(ParseError::unknown("module", label)),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct FlipFlop {
     label: String,
     state: bool,
@@ -68,16 +59,8 @@ impl FlipFlop {
         }
     }
 
-    fn process(
-        &mut self,
-        pulse: bool,
-        iter_map: &mut HashMap<String, Vec<usize>>,
-        i: usize,
-    ) -> Option<bool> {
+    fn process(&mut self, pulse: bool) -> Option<bool> {
         if !pulse {
-            if let Some(v) = iter_map.get_mut(&self.label) {
-                v.push(i)
-            }
             self.state = !self.state;
             Some(self.state)
         } else {
@@ -86,7 +69,7 @@ impl FlipFlop {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Conjunction {
     label: String,
     state: HashMap<String, bool>,
@@ -102,25 +85,13 @@ impl Conjunction {
         }
     }
 
-    fn process(
-        &mut self,
-        source: &str,
-        pulse: bool,
-        iter_map: &mut HashMap<String, Vec<usize>>,
-        i: usize,
-    ) -> bool {
+    fn process(&mut self, source: &str, pulse: bool) -> bool {
         self.state.insert(source.to_string(), pulse);
-        let pulse = !self.state.values().all(|v| *v);
-        if !pulse {
-            if let Some(v) = iter_map.get_mut(&self.label) {
-                v.push(i);
-            }
-        }
-        pulse
+        !self.state.values().all(|v| *v)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Broadcaster {
     label: String,
     destinations: Vec<String>,
@@ -139,7 +110,7 @@ impl Broadcaster {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Output {
     destinations: Vec<String>,
     label: String,
@@ -152,15 +123,9 @@ impl Output {
             label: label.to_string(),
         }
     }
-
-    fn process(&self, pulse: bool, i: usize) {
-        if !pulse {
-            panic!("Winner at {i}")
-        }
-    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Module {
     F(FlipFlop),
     C(Conjunction),
@@ -186,179 +151,468 @@ impl Module {
         }
     }
 
-    fn process(
-        &mut self,
-        source: &str,
-        pulse: bool,
-        iter_map: &mut HashMap<String, Vec<usize>>,
-        i: usize,
-    ) -> Option<bool> {
+    /// The label as written in the input, with the type prefix
+    fn typed_label(&self) -> String {
         match self {
-            Module::F(f) => f.process(pulse, iter_map, i),
-            Module::C(c) => Some(c.process(source, pulse, iter_map, i)),
+            Module::F(f) => format!("%{}", f.label),
+            Module::C(c) => format!("&{}", c.label),
+            _ => self.label().to_string(),
+        }
+    }
+
+    fn process(&mut self, source: &str, pulse: bool) -> Option<bool> {
+        match self {
+            Module::F(f) => f.process(pulse),
+            Module::C(c) => Some(c.process(source, pulse)),
             Module::B(b) => Some(b.process()),
-            Module::O(o) => {
-                o.process(pulse, i);
-                None
-            }
+            Module::O(_) => None,
         }
     }
 }
 
 type ModuleGraph = DiGraph<String, bool, u32>;
 
-fn label_to_key(label: &str) -> String {
-    if matches!(label.chars().next(), Some('%') | Some('&')) {
-        &label[1..]
-    } else {
-        label
-    }
-    .to_string()
-}
-
 fn label_to_nodeindex(
     label: &str,
+    typed_label: String,
     node_map: &mut HashMap<String, NodeIndex>,
     g: &mut ModuleGraph,
 ) -> NodeIndex {
     *node_map
-        .entry(label_to_key(label))
-        .or_insert_with(|| g.add_node(label.to_string()))
-}
-
-fn make_edges(input: &str, node_map: &mut HashMap<String, NodeIndex>, g: &mut ModuleGraph) {
-    if let Some((source, destinations)) = input.split_once(" -> ") {
-        let source = node_map[&label_to_key(source)];
-        for dest in destinations.split(", ") {
-            let dest = node_map
-                .get(dest)
-                .unwrap_or_else(|| panic!("no entry found for key {dest}"));
-            g.add_edge(source, *dest, false);
-        }
-    } else {
-        panic!("bad module {input}")
-    }
+        .entry(label.to_string())
+        .or_insert_with(|| g.add_node(typed_label))
 }
 
-fn make_graph(input: &str) -> ModuleGraph {
+fn make_graph(modules: &[Module]) -> ModuleGraph {
     let mut g = DiGraph::new();
     let mut node_map = HashMap::new();
 
-    for line in input.lines() {
-        if let Some((source, _)) = line.split_once(" -> ") {
-            label_to_nodeindex(source, &mut node_map, &mut g);
-        } else {
-            panic!("bad module {line}")
-        }
+    for module in modules {
+        label_to_nodeindex(module.label(), module.typed_label(), &mut node_map, &mut g);
     }
-    node_map.insert(RX.to_string(), g.add_node(RX.to_string()));
 
-    input
-        .lines()
-        .for_each(|line| make_edges(line, &mut node_map, &mut g));
+    for module in modules {
+        let source = node_map[module.label()];
+        for dest in module.destinations() {
+            // Untyped modules like `rx` or the examples' `output` only receive pulses
+            let dest = label_to_nodeindex(dest, dest.to_string(), &mut node_map, &mut g);
+            g.add_edge(source, dest, false);
+        }
+    }
     g
 }
 
 const RX: &str = "rx";
 
-fn lcm(a: usize, b: usize) -> usize {
-    (a * b) / gcd(a, b)
+/// Presses after which part 2 gives up on the inputs of the conjunction in front of `rx` ever
+/// repeating
+const MAX_PRESSES: usize = 1 << 20;
+
+/// The label without the `%` or `&` of its type
+fn untyped(typed_label: &str) -> &str {
+    typed_label.trim_start_matches(['%', '&'])
+}
+
+/// A pulse sent during the `press`th button press, the first press is 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub source: String,
+    pub dest: String,
+    pub pulse: bool,
+    pub press: usize,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pulse = if self.pulse { "high" } else { "low" };
+        write!(f, "{} {} -{pulse}-> {}", self.press, self.source, self.dest)
+    }
 }
 
-fn parts(input: &str, part2: bool) -> usize {
-    let presses = if part2 { 10000 } else { 1000 };
+/// Watches the pulses of a [`Network`], in the order they are sent
+pub trait Subscriber {
+    fn on_pulse(&mut self, event: &Event);
+}
 
-    let modules: Vec<Module> = input
-        .lines()
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .unwrap();
+impl<F: FnMut(&Event)> Subscriber for F {
+    fn on_pulse(&mut self, event: &Event) {
+        self(event)
+    }
+}
 
-    let mut modules: HashMap<String, Module> = modules
-        .into_iter()
-        .map(|m| (m.label().to_string(), m))
-        .collect();
-    // dbg!(&modules);
-
-    let source_dest_pairs: Vec<(String, String)> = modules
-        .values()
-        .flat_map(|m| {
-            m.destinations()
-                .iter()
-                .map(|dest| (m.label().to_string(), dest.to_string()))
-        })
-        .collect();
-    for (source, dest) in source_dest_pairs {
-        if let Some(Module::C(c)) = modules.get_mut(&dest) {
-            c.state.insert(source, false);
-        }
+/// Records the pulses of a [`Network`], to read them back or write them out
+#[derive(Debug, Default)]
+pub struct Trace {
+    events: Vec<Event>,
+}
+
+impl Trace {
+    pub fn events(&self) -> &[Event] {
+        &self.events
     }
 
-    modules.insert(RX.to_string(), Module::O(Output::new(RX)));
+    /// Writes the recorded pulses on lines like `1 button -low-> broadcaster` and forgets them,
+    /// so a long run can be written out a press at a time
+    pub fn write_to(&mut self, writer: &mut impl Write) -> io::Result<()> {
+        self.events
+            .drain(..)
+            .try_for_each(|event| writeln!(writer, "{event}"))
+    }
+}
+
+impl Subscriber for Trace {
+    fn on_pulse(&mut self, event: &Event) {
+        self.events.push(event.clone());
+    }
+}
 
-    let mut queue: VecDeque<(String, String, bool)> = VecDeque::new();
-    let mut pulse_neg = 0;
-    let mut pulse_pos = 0;
+/// So the trace can be read once the network is done with it. To read it between presses, share
+/// it in an `Rc<RefCell<Trace>>` and subscribe a closure borrowing it
+impl Subscriber for &mut Trace {
+    fn on_pulse(&mut self, event: &Event) {
+        (**self).on_pulse(event)
+    }
+}
 
-    let mut iter_map: HashMap<String, Vec<usize>> = HashMap::new();
-    iter_map.insert("nl".to_string(), vec![]);
-    iter_map.insert("dj".to_string(), vec![]);
-    iter_map.insert("rr".to_string(), vec![]);
-    iter_map.insert("pb".to_string(), vec![]);
+/// The modules of a circuit with their state, pulsing as the button is pressed
+pub struct Network<'a> {
+    initial: HashMap<String, Module>,
+    modules: HashMap<String, Module>,
+    presses: usize,
+    subscribers: Vec<Box<dyn Subscriber + 'a>>,
+}
 
-    for i in 0..presses {
-        queue.clear();
-        queue.push_back(("".to_string(), "broadcaster".to_string(), false));
+impl<'a> Network<'a> {
+    pub fn new(Modules(modules): &Modules) -> Self {
+        Self::wire(modules)
+    }
 
-        while let Some((source, dest, pulse)) = queue.pop_front() {
-            if pulse {
-                pulse_pos += 1;
-            } else {
-                pulse_neg += 1;
+    /// Every conjunction remembers a low pulse from each of its inputs
+    fn wire(modules: &[Module]) -> Self {
+        let mut modules: HashMap<String, Module> = modules
+            .iter()
+            .map(|m| (m.label().to_string(), m.clone()))
+            .collect();
+
+        let source_dest_pairs: Vec<(String, String)> = modules
+            .values()
+            .flat_map(|m| {
+                m.destinations()
+                    .iter()
+                    .map(|dest| (m.label().to_string(), dest.to_string()))
+            })
+            .collect();
+        for (source, dest) in source_dest_pairs {
+            if let Some(Module::C(c)) = modules.get_mut(&dest) {
+                c.state.insert(source, false);
             }
+        }
+
+        modules.insert(RX.to_string(), Module::O(Output::new(RX)));
+        Self {
+            initial: modules.clone(),
+            modules,
+            presses: 0,
+            subscribers: vec![],
+        }
+    }
 
-            if let Some(m) = &mut modules.get_mut(&dest) {
-                if let Some(pulse) = m.process(&source, pulse, &mut iter_map, i + 1) {
+    /// Gets every pulse sent from now on
+    pub fn subscribe(&mut self, subscriber: impl Subscriber + 'a) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    /// How often the button was pressed since the start or the last `reset`
+    pub fn presses(&self) -> usize {
+        self.presses
+    }
+
+    /// Pushes the button once, the subscribers see the pulses it sets off
+    pub fn press(&mut self) {
+        self.presses += 1;
+        let mut queue = VecDeque::new();
+        queue.push_back(Event {
+            source: "button".to_string(),
+            dest: "broadcaster".to_string(),
+            pulse: false,
+            press: self.presses,
+        });
+
+        while let Some(event) = queue.pop_front() {
+            for subscriber in &mut self.subscribers {
+                subscriber.on_pulse(&event);
+            }
+            if let Some(m) = self.modules.get_mut(&event.dest) {
+                if let Some(pulse) = m.process(&event.source, event.pulse) {
                     for new_dest in m.destinations() {
-                        queue.push_back((dest.to_string(), new_dest.to_string(), pulse))
+                        queue.push_back(Event {
+                            source: event.dest.clone(),
+                            dest: new_dest.clone(),
+                            pulse,
+                            press: self.presses,
+                        });
                     }
                 }
             }
         }
     }
 
-    let deltas = iter_map
-        .into_values()
-        .map(|values| {
-            once(0)
-                .chain(values)
-                .tuple_windows()
-                .map(|(a, b)| b - a)
-                .collect_vec()
-        })
-        .collect_vec();
+    /// A hash of what the flip-flops and conjunctions remember, equal whenever the network is in
+    /// the same state, to find presses after which it repeats
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for label in self.modules.keys().sorted() {
+            match &self.modules[label] {
+                Module::F(f) => (label, f.state).hash(&mut hasher),
+                Module::C(c) => (label, c.state.iter().sorted().collect_vec()).hash(&mut hasher),
+                Module::B(_) | Module::O(_) => (),
+            }
+        }
+        hasher.finish()
+    }
 
-    if part2 {
-        deltas.iter().map(|v| v[0]).fold(1, lcm)
-    } else {
-        pulse_pos * pulse_neg
+    /// Back to the state before the first press, the subscribers stay
+    pub fn reset(&mut self) {
+        self.modules = self.initial.clone();
+        self.presses = 0;
     }
 }
 
-fn part1(input: &str) -> usize {
-    parts(input, false)
+fn pulse_product(modules: &[Module]) -> u64 {
+    let mut pulses = [0_u64, 0];
+    let mut network = Network::wire(modules);
+    network.subscribe(|event: &Event| pulses[usize::from(event.pulse)] += 1);
+    for _ in 0..1000 {
+        network.press();
+    }
+    drop(network);
+    pulses[0] * pulses[1]
 }
 
-fn part2(input: &str) -> usize {
-    parts(input, true)
+/// The conjunction that is the only module sending to `rx`, with the modules sending to it
+fn rx_feeder(g: &ModuleGraph) -> Option<(String, Vec<String>)> {
+    let rx = g.node_indices().find(|node| g[*node] == RX)?;
+    let (feeder,) = g
+        .neighbors_directed(rx, Direction::Incoming)
+        .collect_tuple()?;
+    if !g[feeder].starts_with('&') {
+        return None;
+    }
+    let inputs = g
+        .neighbors_directed(feeder, Direction::Incoming)
+        .map(|input| untyped(&g[input]).to_string())
+        .collect();
+    Some((untyped(&g[feeder]).to_string(), inputs))
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(20)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+/// The `n` that leaves each `residue` modulo its `modulus`, as the smallest such `n` and the lcm
+/// of the moduli, which need not be coprime. `None` if there is no such `n`
+fn crt(congruences: impl IntoIterator<Item = (i128, i128)>) -> Option<(i128, i128)> {
+    congruences.into_iter().try_fold(
+        (0, 1),
+        |(residue, modulus), (other_residue, other_modulus)| {
+            let ExtendedGcd { gcd, x, .. } = modulus.extended_gcd(&other_modulus);
+            let difference = other_residue - residue;
+            if difference % gcd != 0 {
+                return None;
+            }
+            let lcm = modulus / gcd * other_modulus;
+            let k = (difference / gcd * x).rem_euclid(other_modulus / gcd);
+            Some(((residue + modulus * k).rem_euclid(lcm), lcm))
+        },
+    )
+}
+
+/// Watches `rx` and the conjunction in front of it
+struct RxWatch {
+    feeder: Option<String>,
+    /// The press in which `rx` first got a low pulse
+    rx_low: Option<usize>,
+    /// For each input of the feeder, the first three presses in which it sent a high pulse
+    highs: HashMap<String, Vec<usize>>,
+}
+
+impl Subscriber for RxWatch {
+    fn on_pulse(&mut self, event: &Event) {
+        if event.dest == RX && !event.pulse {
+            self.rx_low.get_or_insert(event.press);
+        } else if event.pulse && self.feeder.as_ref() == Some(&event.dest) {
+            if let Some(presses) = self.highs.get_mut(&event.source) {
+                if presses.len() < 3 && presses.last() != Some(&event.press) {
+                    presses.push(event.press);
+                }
+            }
+        }
+    }
+}
+
+/// The first press in which all inputs send a high pulse, if each does so periodically
+fn first_common_press(highs: &HashMap<String, Vec<usize>>) -> Option<u64> {
+    let mut cycles = vec![];
+    for presses in highs.values() {
+        let (first, second, third) = (presses[0], presses[1], presses[2]);
+        if second - first != third - second {
+            return None;
+        }
+        cycles.push((first, second - first));
+    }
+    let latest = cycles.iter().map(|(first, _)| *first).max()? as i128;
+    let congruences = cycles
+        .iter()
+        .map(|&(first, period)| (first as i128, period as i128));
+    let (n, lcm) = crt(congruences)?;
+    let n = n + Integer::div_ceil(&(latest - n).max(0), &lcm) * lcm;
+    u64::try_from(n).ok()
+}
+
+/// The first press that sends a low pulse to `rx`. Unless it comes early, that's when every input
+/// of the conjunction in front of `rx` sends it a high pulse during the same press. Each of them
+/// does so periodically, from the presses of its first three high pulses the offset and period
+/// are combined into the first press they all have in common. Without that conjunction this
+/// just presses the button until `rx` gets a low pulse
+fn presses_to_rx(modules: &[Module]) -> Option<u64> {
+    let g = make_graph(modules);
+    if !g.node_weights().any(|label| label == RX) {
+        return None;
+    }
+    let (feeder, inputs) = rx_feeder(&g).unzip();
+    let watch = Rc::new(RefCell::new(RxWatch {
+        feeder,
+        rx_low: None,
+        highs: inputs
+            .into_iter()
+            .flatten()
+            .map(|input| (input, vec![]))
+            .collect(),
+    }));
+    let mut network = Network::wire(modules);
+    let watching = Rc::clone(&watch);
+    network.subscribe(move |event: &Event| watching.borrow_mut().on_pulse(event));
+
+    for _ in 0..MAX_PRESSES {
+        network.press();
+        let watch = watch.borrow();
+        if let Some(press) = watch.rx_low {
+            return Some(press as u64);
+        }
+        if !watch.highs.is_empty() && watch.highs.values().all(|presses| presses.len() == 3) {
+            return first_common_press(&watch.highs);
+        }
+    }
+    None
+}
 
-    Ok(())
+pub fn part1(input: &str) -> u64 {
+    Day20.part1(&Day20.parse(input).unwrap())
+}
+
+pub fn part2(input: &str) -> u64 {
+    Day20.part2(&Day20.parse(input).unwrap()).unwrap()
+}
+
+/// The modules in the order of the input
+pub struct Modules(Vec<Module>);
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input<'a> = Modules;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(&self, input: &str) -> Result<Modules, ParseError> {
+        let modules: Vec<Module> = parse_lines(input, str::parse)?;
+        if !modules.iter().any(|m| matches!(m, Module::B(_))) {
+            return Err(ParseError::missing("broadcaster"));
+        }
+        Ok(Modules(modules))
+    }
+
+    fn part1(&self, Modules(modules): &Modules) -> u64 {
+        pulse_product(modules)
+    }
+
+    /// `None` for circuits like the examples, without `rx`
+    fn part2(&self, Modules(modules): &Modules) -> Option<u64> {
+        presses_to_rx(modules)
+    }
+
+    fn dot(&self, Modules(modules): &Modules) -> Option<String> {
+        let g = make_graph(modules);
+        Some(Dot::with_config(&g, &[Config::EdgeNoLabel]).to_string())
+    }
+}
+
+const COUNTER_BITS: usize = 12;
+
+/// The most counters `generate` makes: part 2 multiplies a period below `2^COUNTER_BITS` per
+/// counter, which has to fit in the u64 answer
+pub const MAX_COUNTERS: usize = u64::BITS as usize / COUNTER_BITS;
+
+fn is_prime(n: usize) -> bool {
+    n > 1
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !n.is_multiple_of(d))
+}
+
+/// `size` distinct primes with `COUNTER_BITS` bits
+fn periods(size: usize, rng: &mut Rng) -> Vec<usize> {
+    let mut periods: Vec<usize> = vec![];
+    while periods.len() < size {
+        let period = rng.range(1 << (COUNTER_BITS - 1)..1 << COUNTER_BITS) as usize;
+        if is_prime(period) && !periods.contains(&period) {
+            periods.push(period);
+        }
+    }
+    periods
+}
+
+/// `size` counters like the ones in the puzzle input: a chain of flip-flops counting the button
+/// presses and a conjunction that resets them with a low pulse after a different prime number of
+/// presses, inverted into the conjunction in front of `rx`
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    assert!(
+        size <= MAX_COUNTERS,
+        "the part 2 answer of more than {MAX_COUNTERS} counters doesn't fit in a u64"
+    );
+    let mut names = rng
+        .names(size * (COUNTER_BITS + 2) + 1, 2, &[RX])
+        .into_iter();
+    let mut next_name = || names.next().unwrap();
+    let periods = periods(size, rng);
+
+    let feeder = next_name();
+    let mut lines = vec![format!("&{feeder} -> {RX}")];
+    let mut first_bits = vec![];
+    for period in periods {
+        let bits = (0..COUNTER_BITS).map(|_| next_name()).collect_vec();
+        let (counter, inverter) = (next_name(), next_name());
+        let is_set = |i: usize| (period >> i) & 1 == 1;
+        for (i, bit) in bits.iter().enumerate() {
+            let mut destinations = bits.get(i + 1).into_iter().cloned().collect_vec();
+            if is_set(i) {
+                destinations.push(counter.clone());
+            }
+            rng.shuffle(&mut destinations);
+            lines.push(format!("%{bit} -> {}", destinations.join(", ")));
+        }
+        // Adding the unset bits and 1 wraps the counter around to 0
+        let mut resets = once(inverter.clone())
+            .chain(
+                (0..COUNTER_BITS)
+                    .filter(|i| *i == 0 || !is_set(*i))
+                    .map(|i| bits[i].clone()),
+            )
+            .collect_vec();
+        rng.shuffle(&mut resets);
+        lines.push(format!("&{counter} -> {}", resets.join(", ")));
+        lines.push(format!("&{inverter} -> {feeder}"));
+        first_bits.push(bits[0].clone());
+    }
+    lines.push(format!("broadcaster -> {}", first_bits.join(", ")));
+    rng.shuffle(&mut lines);
+    lines.join("\n")
 }
 
 #[test]
@@ -382,10 +636,14 @@ fn test_module_parse() {
     }
 
     let invalid_str = "invalid format";
-    assert!(matches!(
-        invalid_str.parse::<Module>(),
-        Err(ParseModuleError::InvalidFormat(_))
-    ));
+    assert_eq!(
+        invalid_str.parse::<Module>().unwrap_err().to_string(),
+        "expected [module] -> [destinations], found 'invalid format'"
+    );
+    assert_eq!(
+        "$a -> b".parse::<Module>().unwrap_err().to_string(),
+        "unknown module '$a'"
+    );
 }
 
 #[test]
@@ -396,6 +654,7 @@ fn example() {
 %c -> inv
 &inv -> a";
     assert_eq!(part1(input), 32000000);
+    assert_eq!(Day20.part2(&Day20.parse(input).unwrap()), None);
 }
 
 #[test]
@@ -406,20 +665,145 @@ fn example2() {
 %b -> con
 &con -> output";
     assert_eq!(part1(input), 11687500);
+
+    let dot = Day20.dot(&Day20.parse(input).unwrap()).unwrap();
+    assert!(
+        dot.starts_with("digraph {\n    0 [ label = \"broadcaster\" ]\n    1 [ label = \"%a\" ]\n")
+    );
+    assert!(dot.contains("[ label = \"output\" ]"));
+    assert_eq!(dot.matches(" -> ").count(), 6);
 }
 
 #[test]
-fn task() {
-    let input = &read_input_to_string(20).unwrap();
-    assert_eq!(part1(input), 898731036);
-    assert_eq!(part2(input), 229414480926893);
+fn trace() {
+    let input = "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a";
+    let modules = Day20.parse(input).unwrap();
+    let mut trace = Trace::default();
+    let mut lows = 0;
+    let mut network = Network::new(&modules);
+    network.subscribe(&mut trace);
+    network.subscribe(|event: &Event| lows += usize::from(!event.pulse));
+    network.press();
+    drop(network);
+
+    assert_eq!(trace.events().len(), 12);
+    assert_eq!(lows, 8);
+    let mut written = vec![];
+    trace.write_to(&mut written).unwrap();
+    assert_eq!(
+        String::from_utf8(written).unwrap(),
+        "1 button -low-> broadcaster
+1 broadcaster -low-> a
+1 broadcaster -low-> b
+1 broadcaster -low-> c
+1 a -high-> b
+1 b -high-> c
+1 c -high-> inv
+1 inv -low-> a
+1 a -low-> b
+1 b -low-> c
+1 c -low-> inv
+1 inv -high-> a
+"
+    );
+    assert!(trace.events().is_empty());
+}
+
+#[test]
+fn network_cycle() {
+    let input = "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output";
+    let mut network = Network::new(&Day20.parse(input).unwrap());
+    let initial = network.state_hash();
+    let hashes = (0..4)
+        .map(|_| {
+            network.press();
+            network.state_hash()
+        })
+        .collect_vec();
+    assert_eq!(
+        hashes
+            .iter()
+            .positions(|hash| *hash == initial)
+            .collect_vec(),
+        [3]
+    );
+    assert_eq!(hashes.iter().unique().count(), 4);
+
+    assert_eq!(network.presses(), 4);
+    network.reset();
+    assert_eq!(network.presses(), 0);
+    assert_eq!(network.state_hash(), initial);
+}
+
+#[test]
+fn generated() {
+    let input = generate(MAX_COUNTERS, &mut Rng::new(20));
+    assert_eq!(input.lines().count(), MAX_COUNTERS * (COUNTER_BITS + 2) + 2);
+    let Modules(modules) = Day20.parse(&input).unwrap();
+    assert_eq!(
+        make_graph(&modules).node_count(),
+        MAX_COUNTERS * (COUNTER_BITS + 2) + 3
+    );
+    assert!(Day20.part1(&Modules(modules)) > 0);
+
+    let rng = &mut Rng::new(20);
+    rng.names(MAX_COUNTERS * (COUNTER_BITS + 2) + 1, 2, &[RX]);
+    assert_eq!(
+        part2(&input),
+        periods(MAX_COUNTERS, rng)
+            .iter()
+            .map(|&period| period as u64)
+            .product::<u64>()
+    );
 }
 
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(20).unwrap();
-        part1(input);
-        part2(input);
-    })
+#[test]
+fn test_crt() {
+    assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+    assert_eq!(crt([(1, 4), (3, 6)]), Some((9, 12)));
+    assert_eq!(crt([(0, 4), (1, 6)]), None);
+}
+
+#[test]
+fn rx_feeder_and_offsets() {
+    // `a` sends a high pulse to `x` on every even press and `b` on presses 2, 6, 10 and so on
+    let input = "broadcaster -> a1, b1
+%a1 -> a
+&a -> x
+%b1 -> b2
+%b2 -> b3
+&b3 -> b
+&b -> x
+&x -> rx";
+    let Modules(modules) = Day20.parse(input).unwrap();
+    assert_eq!(
+        rx_feeder(&make_graph(&modules)),
+        Some(("x".to_string(), vec!["b".to_string(), "a".to_string()]))
+    );
+    assert_eq!(part2(input), 2);
+
+    // `a` and `b` send their high pulses on different presses, but `x` remembers the one from `b`
+    // on press 2, and sends `rx` a low pulse with the one from `a` on press 3
+    let input = "broadcaster -> a, b1
+%a -> x
+%b1 -> b
+%b -> x
+&x -> rx";
+    assert_eq!(part2(input), 3);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn task() {
+    let input = &read_input_to_string(20).unwrap();
+    assert_answer(20, 1, part1(input));
+    assert_answer(20, 2, part2(input));
 }
@@ -1,218 +1,72 @@
-extern crate test;
+//! [Day 21: Step Counter](https://adventofcode.com/2023/day/21)
 
 use itertools::Itertools;
-use std::collections::VecDeque;
-use std::ops::{Add, AddAssign, Sub};
 
-use crate::day21::Direction::{Down, Left, Right, Up};
-use ndarray::{s, Array2, Dim};
-#[cfg(test)]
-use test::Bencher;
+use crate::parse::{ParseError, ParseResult};
+use crate::solution::Solution;
+use crate::utils::grid::Direction::{Down, Right};
+use crate::utils::grid::{parse_grid, Direction, Grid, Position};
+use crate::utils::search::{bfs, Search, Visit};
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
+use crate::visualize::{Rgb, Scene};
 
-use crate::utils::{parse_board, pretty_print, read_input_to_string};
-
-type Board = Array2<char>;
-
-fn pad(arr: &Board, value: char) -> Board {
-    // janky pad implementation
-    let mut board = Array2::from_elem(arr.raw_dim() + Dim([2, 2]), value);
-    board
-        .slice_mut(s![1..board.shape()[0] - 1, 1..board.shape()[1] - 1])
-        .assign(arr);
-    board
-}
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Position {
-    x: isize,
-    y: isize,
-}
-
-impl Position {
-    fn new(x: isize, y: isize) -> Self {
-        Self { x, y }
-    }
-
-    fn from_index(ix: (usize, usize)) -> Self {
-        Self::new(ix.1 as isize, ix.0 as isize)
-    }
-
-    fn to_index(self) -> [usize; 2] {
-        [self.y as usize, self.x as usize]
-    }
-
-    fn to_index_wrapped(self, board_size: (usize, usize)) -> [usize; 2] {
-        let wrapped_x = (self.x.rem_euclid(board_size.0 as isize)) as usize;
-        let wrapped_y = (self.y.rem_euclid(board_size.1 as isize)) as usize;
-        [wrapped_y, wrapped_x]
-    }
-
-    fn board_id(&self, board_size: (usize, usize)) -> (isize, isize) {
-        (
-            self.x.div_euclid(board_size.1 as isize),
-            self.y.div_euclid(board_size.0 as isize),
-        )
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Left,
-    Down,
-    Right,
-}
-
-impl Add<Direction> for Position {
-    type Output = Position;
-
-    fn add(self, dir: Direction) -> Position {
-        match dir {
-            Up => Position {
-                x: self.x,
-                y: self.y - 1,
-            },
-            Left => Position {
-                x: self.x - 1,
-                y: self.y,
-            },
-            Down => Position {
-                x: self.x,
-                y: self.y + 1,
-            },
-            Right => Position {
-                x: self.x + 1,
-                y: self.y,
-            },
-        }
-    }
-}
-
-impl AddAssign<Direction> for Position {
-    fn add_assign(&mut self, other: Direction) {
-        *self = *self + other;
-    }
-}
-
-impl Add for Position {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self::Output {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
-        }
-    }
-}
-
-impl Sub for Position {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self::Output {
-        Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-        }
-    }
-}
-
-impl AddAssign for Position {
-    fn add_assign(&mut self, other: Self) {
-        *self = *self + other;
-    }
-}
+type Board = Grid<char>;
 
 const ROCK: char = '#';
-const PLOT: char = '.';
-const P2_STEPS: usize = 26501365;
-
-const DIRS: [Direction; 4] = [Up, Left, Down, Right];
-
-fn bfs(board: &mut Array2<char>, starts: &[Position], record_steps: &[usize]) -> Vec<usize> {
-    let board_dim = board.dim();
-    let mut queue = VecDeque::new();
-
-    let s_idx = board
-        .indexed_iter()
-        .find_map(|(ix, c)| if *c == 'S' { Some(ix) } else { None })
-        .unwrap();
-    board[s_idx] = PLOT;
-
-    for start in starts {
-        queue.push_back(*start);
-    }
-
-    let steps = record_steps.iter().max().unwrap();
-    let mut visited = Array2::from_elem(
-        (
-            starts[0].y as usize + steps * 2 + 1,
-            starts[0].x as usize + steps * 2 + 1,
-        ),
-        false,
-    );
-
-    let mut out = vec![];
-    let mut level_size;
-    for step in 1..steps + 1 {
-        level_size = queue.len();
-        visited.fill(false);
-        for _ in 0..level_size {
-            let current = queue.pop_front().unwrap();
-
-            for dir in DIRS {
-                let next_pos = current + dir;
-                if !visited[(
-                    (next_pos.y + *steps as isize) as usize,
-                    (next_pos.x + *steps as isize) as usize,
-                )] && board[next_pos.to_index_wrapped(board_dim)] == PLOT
-                {
-                    visited[(
-                        (next_pos.y + *steps as isize) as usize,
-                        (next_pos.x + *steps as isize) as usize,
-                    )] = true;
-                    queue.push_back(next_pos);
-                }
+pub const P1_STEPS: usize = 64;
+pub const P2_STEPS: usize = 26501365;
+
+/// Steps from the closest start to every plot at most `max_steps` away, on a board that repeats
+/// in every direction
+fn plot_distances(board: &Board, starts: &[Position], max_steps: usize) -> Search<Position, usize> {
+    bfs(
+        starts.iter().copied(),
+        |pos| {
+            Direction::ALL
+                .map(|dir| *pos + dir)
+                .into_iter()
+                .filter(|next| *board.get_wrapped(*next) != ROCK)
+        },
+        |_, steps| {
+            if steps == max_steps {
+                Visit::Prune
+            } else {
+                Visit::Continue
             }
-        }
-        if record_steps.contains(&step) {
-            out.push(visited.iter().filter(|v| **v).count())
-        }
-    }
-    out
+        },
+    )
 }
 
-#[allow(dead_code)]
-fn visualize(board: &Board, reached: &[Position]) {
-    let mut board = board.clone();
-
-    for pos in reached {
-        board[pos.to_index()] = 'O';
-    }
+/// Stepping back and forth is allowed, so a plot can be reached in exactly `steps` if it is at
+/// most that many steps away, with an even difference
+fn reachable_in(distance: usize, steps: usize) -> bool {
+    distance <= steps && distance % 2 == steps % 2
+}
 
-    pretty_print(&board.view());
+/// How many plots can be reached in exactly each of `record_steps`
+fn reachable_counts(board: &Board, starts: &[Position], record_steps: &[usize]) -> Vec<usize> {
+    let search = plot_distances(board, starts, *record_steps.iter().max().unwrap());
+    record_steps
+        .iter()
+        .map(|&steps| {
+            search
+                .reached()
+                .filter(|&(_, distance)| reachable_in(distance, steps))
+                .count()
+        })
+        .collect()
 }
 
 fn walled_board(board: &Board, starts: &[Position], steps: usize) -> usize {
-    let mut board = pad(board, ROCK);
-    *bfs(&mut board, starts, &[steps]).first().unwrap()
+    let board = board.padded(1, ROCK);
+    *reachable_counts(&board, starts, &[steps]).first().unwrap()
 }
 
-fn part1(input: &str, steps: usize) -> usize {
-    let board = parse_board(input);
-    let start = board
-        .indexed_iter()
-        .find_map(|(ix, c)| {
-            if *c == 'S' {
-                Some(Position::from_index(ix))
-            } else {
-                None
-            }
-        })
-        .unwrap()
-        + Down
-        + Right;
+fn reachable_walled(board: &Board, steps: usize) -> usize {
+    let start = board.position(|c| *c == 'S').unwrap() + Down + Right;
 
-    walled_board(&board, &[start], steps)
+    walled_board(board, &[start], steps)
 }
 
 struct StepIterator<T> {
@@ -268,8 +122,9 @@ fn gauss_jordan(matrix: &[[f64; 3]; 3], y_values: &mut [f64; 3]) -> Option<[f64;
 
         for j in (i + 1)..3 {
             let ratio = mat[j][i] / mat[i][i];
-            for k in 0..3 {
-                mat[j][k] -= ratio * mat[i][k];
+            let pivot_row = mat[i];
+            for (value, pivot) in mat[j].iter_mut().zip(pivot_row) {
+                *value -= ratio * pivot;
             }
             y_values[j] -= ratio * y_values[i];
         }
@@ -297,50 +152,95 @@ fn zip_to_three_point(vec1: Vec<usize>, vec2: Vec<usize>) -> Option<[(f64, f64);
         .ok() // Convert Result to Option
 }
 
-fn cross_open(array: &Array2<char>, point: [usize; 2]) -> bool {
-    let horizontal_open = array.row(point[0]).iter().all(|&c| c != '#');
-    let vertical_open = array.column(point[1]).iter().all(|&c| c != '#');
+fn cross_open(board: &Board, point: Position) -> bool {
+    let horizontal_open = board
+        .cells()
+        .row(point.y as usize)
+        .iter()
+        .all(|&c| c != '#');
+    let vertical_open = board
+        .cells()
+        .column(point.x as usize)
+        .iter()
+        .all(|&c| c != '#');
 
     horizontal_open && vertical_open
 }
 
-fn part2(input: &str, steps: usize) -> usize {
-    let mut board = parse_board(input);
-    let start = board
-        .indexed_iter()
-        .find_map(|(ix, c)| {
-            if *c == 'S' {
-                Some(Position::from_index(ix))
-            } else {
-                None
-            }
-        })
-        .unwrap();
+fn reachable_repeating(board: &Board, steps: usize) -> u64 {
+    let start = board.position(|c| *c == 'S').unwrap();
 
     // We're in the center of a square board, with free passages to the edges
-    assert!(board.is_square());
-    let board_side = board.dim().0;
+    assert_eq!(board.width(), board.height());
+    let board_side = board.width();
     assert_eq!(board_side % 2, 1); // Odd size -> has a single center
     let center = Position::new((board_side / 2) as isize, (board_side / 2) as isize);
     assert_eq!(center, start);
-    assert!(cross_open(&board, start.to_index())); // Free passage to the edges
+    assert!(cross_open(board, start)); // Free passage to the edges
 
     // Three point method for solving a quadratic
     let to_edge_steps = board_side - 1 - start.x as usize;
     let sample_points = step_by(to_edge_steps, board_side).take(3).collect_vec();
-    let sample_points_reachable = bfs(&mut board, &[start], &sample_points);
+    let sample_points_reachable = reachable_counts(board, &[start], &sample_points);
     let samples = zip_to_three_point(sample_points, sample_points_reachable).unwrap();
     let out = solve_quadratic(samples).unwrap();
 
-    (out.0 * (steps as f64).powi(2) + out.1 * steps as f64 + out.2).round() as usize
+    (out.0 * (steps as f64).powi(2) + out.1 * steps as f64 + out.2).round() as u64
+}
+
+/// Steps to take on the real input, the examples use fewer
+pub struct Day21 {
+    pub part1_steps: usize,
+    pub part2_steps: usize,
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(21)?;
-    dbg!(part1(input, 64));
-    dbg!(part2(input, P2_STEPS));
+impl Solution for Day21 {
+    type Input<'a> = Board;
+    type Answer1 = usize;
+    type Answer2 = u64;
+
+    fn parse(&self, input: &str) -> Result<Board, ParseError> {
+        parse_garden(input)
+    }
+
+    fn part1(&self, board: &Board) -> usize {
+        reachable_walled(board, self.part1_steps)
+    }
 
-    Ok(())
+    fn part2(&self, board: &Board) -> Option<u64> {
+        Some(reachable_repeating(board, self.part2_steps))
+    }
+
+    fn scene(&self, board: &Board) -> Option<Scene> {
+        let start = board.position(|c| *c == 'S').unwrap() + Down + Right;
+        let search = plot_distances(&board.padded(1, ROCK), &[start], self.part1_steps);
+        let reached = search
+            .reached()
+            .filter(|&(_, distance)| reachable_in(distance, self.part1_steps))
+            .map(|(pos, _)| *pos - Position::new(1, 1));
+        Some(Scene::new(board.cells().clone()).layer(
+            &format!("reachable in {} steps", self.part1_steps),
+            Rgb::GREEN,
+            Some('O'),
+            reached,
+        ))
+    }
+}
+
+fn parse_garden(input: &str) -> ParseResult<Board> {
+    let board = parse_grid(input, |c| ".#S".contains(c).then_some(c))?;
+    match board.position(|c| *c == 'S') {
+        Some(_) => Ok(board),
+        None => Err(ParseError::missing("start 'S'")),
+    }
+}
+
+pub fn part1(input: &str, steps: usize) -> usize {
+    reachable_walled(&parse_garden(input).unwrap(), steps)
+}
+
+pub fn part2(input: &str, steps: usize) -> u64 {
+    reachable_repeating(&parse_garden(input).unwrap(), steps)
 }
 
 #[test]
@@ -360,18 +260,10 @@ fn example() {
     assert_eq!(part1(input, 6), 16);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(21).unwrap();
-    assert_eq!(part1(input, 64), 3724);
-    assert_eq!(part2(input, P2_STEPS), 620348631910321);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(21).unwrap();
-        part1(input, 64);
-        part2(input, P2_STEPS);
-    })
+    assert_answer(21, 1, part1(input, P1_STEPS));
+    assert_answer(21, 2, part2(input, P2_STEPS));
 }
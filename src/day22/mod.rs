@@ -1,18 +1,21 @@
-extern crate test;
+//! [Day 22: Sand Slabs](https://adventofcode.com/2023/day/22)
 
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 
-#[cfg(test)]
-use test::Bencher;
-
-use crate::utils::read_input_to_string;
+use crate::parse::{number, parse_lines, split_once, ParseError, ParseResult};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
+#[cfg(all(test, not(target_arch = "wasm32")))]
+use proptest::prelude::*;
 
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::Direction;
 
+#[allow(dead_code)]
 #[derive(Debug, Copy, Clone, PartialEq)]
 struct Position {
     x: usize,
@@ -42,16 +45,12 @@ impl Block {
 }
 
 impl FromStr for Block {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split('~').collect();
-        if parts.len() != 2 {
-            return Err("Input string must have exactly one '~'".into());
-        }
-
-        let start = parse_coordinates(parts[0])?;
-        let end = parse_coordinates(parts[1])?;
+        let (start, end) = split_once(s, "~", "[x],[y],[z]~[x],[y],[z]")?;
+        let start = parse_coordinates(start)?;
+        let end = parse_coordinates(end)?;
 
         Ok(Block {
             x: start.0..=end.0,
@@ -61,24 +60,13 @@ impl FromStr for Block {
     }
 }
 
-fn parse_coordinates(coord: &str) -> Result<(usize, usize, usize), String> {
-    let nums: Vec<&str> = coord.split(',').collect();
-    if nums.len() != 3 {
-        return Err("Each coordinate part must have exactly three numbers".into());
-    }
-
-    let x = nums[0]
-        .parse::<usize>()
-        .map_err(|_| "Invalid number for x")?;
-    let y = nums[1]
-        .parse::<usize>()
-        .map_err(|_| "Invalid number for y")?;
-    let z = nums[2]
-        .parse::<usize>()
-        .map_err(|_| "Invalid number for z")?;
-
-    Ok((x, y, z))
+fn parse_coordinates(coord: &str) -> ParseResult<(usize, usize, usize)> {
+    let Some((x, y, z)) = coord.split(',').collect_tuple() else {
+        return Err(ParseError::format("[x],[y],[z]", coord));
+    };
+    Ok((number(x)?, number(y)?, number(z)?))
 }
+
 fn can_remove<N, E>(graph: &DiGraph<N, E>, node: NodeIndex) -> bool {
     graph
         .neighbors_directed(node, Direction::Incoming)
@@ -106,12 +94,7 @@ fn count_falling<N, E>(graph: &DiGraph<N, E>, node: NodeIndex) -> usize {
     falling.remove(&node);
     falling.len()
 }
-fn create_rests_on_graph(input: &str) -> DiGraph<Block, bool> {
-    let mut blocks: Vec<Block> = input
-        .lines()
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .unwrap();
+fn create_rests_on_graph(mut blocks: Vec<Block>) -> DiGraph<Block, bool> {
     let mut g: DiGraph<Block, bool> = DiGraph::new();
     let mut block_node_map: HashMap<Block, NodeIndex> = HashMap::new();
 
@@ -162,26 +145,38 @@ fn create_rests_on_graph(input: &str) -> DiGraph<Block, bool> {
 
     g
 }
-fn part1(input: &str) -> usize {
-    let g = create_rests_on_graph(input);
+/// Edges point from a block to the blocks it rests on
+pub struct RestsOn(DiGraph<Block, bool>);
 
-    g.node_indices()
-        .filter(|node| can_remove(&g, *node))
-        .count()
-}
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input<'a> = RestsOn;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-fn part2(input: &str) -> usize {
-    let g = create_rests_on_graph(input);
+    fn parse(&self, input: &str) -> Result<RestsOn, ParseError> {
+        Ok(RestsOn(create_rests_on_graph(parse_lines(
+            input,
+            str::parse,
+        )?)))
+    }
+
+    fn part1(&self, RestsOn(g): &RestsOn) -> usize {
+        g.node_indices().filter(|node| can_remove(g, *node)).count()
+    }
 
-    g.node_indices().map(|node| count_falling(&g, node)).sum()
+    fn part2(&self, RestsOn(g): &RestsOn) -> Option<usize> {
+        Some(g.node_indices().map(|node| count_falling(g, node)).sum())
+    }
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(22)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub fn part1(input: &str) -> usize {
+    Day22.part1(&Day22.parse(input).unwrap())
+}
 
-    Ok(())
+pub fn part2(input: &str) -> usize {
+    Day22.part2(&Day22.parse(input).unwrap()).unwrap()
 }
 
 #[test]
@@ -195,7 +190,10 @@ fn test_block_parse() {
     assert_eq!(block.z, 1..=4);
 
     let invalid_input = "1,0~2,3,4";
-    assert!(invalid_input.parse::<Block>().is_err());
+    assert_eq!(
+        invalid_input.parse::<Block>().unwrap_err().to_string(),
+        "expected [x],[y],[z], found '1,0'"
+    );
 }
 
 #[test]
@@ -211,18 +209,107 @@ fn example() {
     assert_eq!(part2(input), 7);
 }
 
+#[cfg(all(test, not(target_arch = "wasm32")))]
+type Cell = [usize; 3];
+
+/// Up to 8 bricks of up to 3 cubes in a 3x3 column, bricks that would overlap an earlier one are
+/// left out
+#[cfg(all(test, not(target_arch = "wasm32")))]
+fn brick_stack() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        (0..3_usize, 0..3_usize, 1..8_usize, 0..3_usize, 0..3_usize),
+        1..=8,
+    )
+    .prop_map(|bricks| {
+        let mut occupied = HashSet::new();
+        let mut lines = vec![];
+        for (x, y, z, axis, len) in bricks {
+            let mut end = [x, y, z];
+            end[axis] += len;
+            let cells = (0..=len).map(|i| {
+                let mut cell = [x, y, z];
+                cell[axis] += i;
+                cell
+            });
+            if cells.clone().all(|cell| !occupied.contains(&cell)) {
+                occupied.extend(cells);
+                lines.push(format!("{x},{y},{z}~{},{},{}", end[0], end[1], end[2]));
+            }
+        }
+        lines.join("\n")
+    })
+}
+
+/// Lowers bricks one cube at a time until none can move, with how many of them fell
+#[cfg(all(test, not(target_arch = "wasm32")))]
+fn settle(bricks: &[Vec<Cell>]) -> (Vec<Vec<Cell>>, usize) {
+    let mut bricks = bricks.to_vec();
+    let mut fell = vec![false; bricks.len()];
+    let mut occupied: HashSet<Cell> = bricks.iter().flatten().copied().collect();
+    loop {
+        let mut moved = false;
+        for (i, brick) in bricks.iter_mut().enumerate() {
+            if brick.iter().any(|cell| cell[2] == 1) {
+                continue;
+            }
+            for cell in brick.iter() {
+                occupied.remove(cell);
+            }
+            let lowered = brick.iter().map(|&[x, y, z]| [x, y, z - 1]).collect_vec();
+            if lowered.iter().all(|cell| !occupied.contains(cell)) {
+                *brick = lowered;
+                fell[i] = true;
+                moved = true;
+            }
+            occupied.extend(brick.iter().copied());
+        }
+        if !moved {
+            return (bricks, fell.iter().filter(|fell| **fell).count());
+        }
+    }
+}
+
+/// Both answers by settling the stack again without each brick
+#[cfg(all(test, not(target_arch = "wasm32")))]
+fn brute_force_disintegration(input: &str) -> (usize, usize) {
+    let bricks = input
+        .lines()
+        .map(|line| {
+            let block: Block = line.parse().unwrap();
+            itertools::iproduct!(block.x, block.y, block.z)
+                .map(|(x, y, z)| [x, y, z])
+                .collect_vec()
+        })
+        .collect_vec();
+    let (settled, _) = settle(&bricks);
+    let falling = (0..settled.len())
+        .map(|i| {
+            let mut rest = settled.clone();
+            rest.remove(i);
+            settle(&rest).1
+        })
+        .collect_vec();
+    (
+        falling.iter().filter(|count| **count == 0).count(),
+        falling.iter().sum(),
+    )
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+proptest! {
+    #[test]
+    fn matches_brute_force(input in brick_stack()) {
+        let rests_on = Day22.parse(&input).unwrap();
+        let (safe, falling) = brute_force_disintegration(&input);
+        prop_assert_eq!(Day22.part1(&rests_on), safe);
+        prop_assert_eq!(Day22.part2(&rests_on), Some(falling));
+    }
+}
+
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(22).unwrap();
-    assert_eq!(part1(input), 465);
-    assert_eq!(part2(input), 79042);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(22).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(22, 1, part1(input));
+    assert_answer(22, 2, part2(input));
 }
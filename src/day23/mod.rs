@@ -1,106 +1,22 @@
-extern crate test;
+//! [Day 23: A Long Walk](https://adventofcode.com/2023/day/23)
 
-use itertools::Itertools;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::hash::Hash;
-use std::ops::{Add, AddAssign, Sub};
 
-use crate::day23::Direction::{Down, Left, Right, Up};
-use ndarray::{s, Array2, Dim};
-use petgraph::algo::all_simple_paths;
 use petgraph::dot::Dot;
-use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex, UnGraph};
-#[cfg(test)]
-use test::Bencher;
+use petgraph::graph::{DiGraph, Graph, NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
 
-use crate::utils::{parse_board, pretty_string, read_input_to_string};
+use crate::parse::ParseError;
+use crate::solution::Solution;
+use crate::utils::grid::Direction::{self, Down, Left, Right, Up};
+use crate::utils::grid::{parse_grid, Grid, Position};
+use crate::utils::parallel::par_map;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
+use crate::visualize::{Rgb, Scene};
 
-type Board = Array2<char>;
-
-const DIRS: [Direction; 4] = [Up, Left, Down, Right];
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Left,
-    Down,
-    Right,
-}
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Position {
-    x: usize,
-    y: usize,
-}
-
-impl Position {
-    fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
-    }
-
-    fn to_index(self) -> [usize; 2] {
-        [self.y, self.x]
-    }
-}
-
-impl Add<Direction> for Position {
-    type Output = Position;
-
-    fn add(self, dir: Direction) -> Position {
-        match dir {
-            Up => Position {
-                x: self.x,
-                y: self.y - 1,
-            },
-            Left => Position {
-                x: self.x - 1,
-                y: self.y,
-            },
-            Down => Position {
-                x: self.x,
-                y: self.y + 1,
-            },
-            Right => Position {
-                x: self.x + 1,
-                y: self.y,
-            },
-        }
-    }
-}
-
-impl AddAssign<Direction> for Position {
-    fn add_assign(&mut self, other: Direction) {
-        *self = *self + other;
-    }
-}
-
-impl Add for Position {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self::Output {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
-        }
-    }
-}
-
-impl Sub for Position {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self::Output {
-        Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-        }
-    }
-}
-
-impl AddAssign for Position {
-    fn add_assign(&mut self, other: Self) {
-        *self = *self + other;
-    }
-}
+type Board = Grid<char>;
 
 fn arrow_dir(c: char) -> Option<Direction> {
     match c {
@@ -116,16 +32,16 @@ const STEP_CHARS: [char; 5] = ['.', '^', '<', '>', 'v'];
 const ARROWS: [char; 4] = ['^', '<', '>', 'v'];
 
 fn valid_neighbors(pos: Position, board: &Board) -> Vec<Position> {
-    let dirs = if let Some(dir) = arrow_dir(board[pos.to_index()]) {
+    let dirs = if let Some(dir) = arrow_dir(board[pos]) {
         vec![dir]
     } else {
-        DIRS.to_vec()
+        Direction::ALL.to_vec()
     };
 
     dirs.iter()
         .filter_map(|dir| {
             let next_pos = pos + *dir;
-            let next_char = board[next_pos.to_index()];
+            let next_char = board[next_pos];
             if STEP_CHARS.contains(&next_char) {
                 Some(next_pos)
             } else if next_char == '#' {
@@ -156,18 +72,18 @@ fn valid_destinations(
     visited: &HashSet<Position>,
 ) -> (Vec<(Position, usize)>, bool) {
     let mut backtrack = true;
-    let dirs = if let Some(dir) = arrow_dir(board[pos.to_index()]) {
+    let dirs = if let Some(dir) = arrow_dir(board[pos]) {
         backtrack = false;
         vec![dir]
     } else {
-        DIRS.to_vec()
+        Direction::ALL.to_vec()
     };
 
     (
         dirs.iter()
             .filter_map(|dir| {
                 let next_pos = pos + *dir;
-                let next_char = board[next_pos.to_index()];
+                let next_char = board[next_pos];
                 if STEP_CHARS.contains(&next_char) {
                     let (next_pos, distance) = fast_forward(next_pos, pos, board);
                     if visited.contains(&next_pos) {
@@ -186,36 +102,6 @@ fn valid_destinations(
     )
 }
 
-fn pad(arr: &Board, value: char) -> Board {
-    // janky pad implementation
-    let mut board = Array2::from_elem(arr.raw_dim() + Dim([2, 2]), ' ');
-    board.fill(value);
-    board
-        .slice_mut(s![1..board.shape()[0] - 1, 1..board.shape()[1] - 1])
-        .assign(arr);
-    board
-}
-
-#[allow(dead_code)]
-fn visualize(board: &Board, path: &[Position]) {
-    let mut board = board.clone();
-
-    for pos in path {
-        board[pos.to_index()] = 'O'
-    }
-    println!("{}", pretty_string(&board.view()));
-}
-
-fn path_length(path: &[NodeIndex], graph: &GraphType) -> usize {
-    path.iter().tuple_windows().fold(0, |acc, (a, b)| {
-        if let Some(edge) = graph.find_edge(*a, *b) {
-            acc + graph.get_edge_distance(edge)
-        } else {
-            panic!("wut")
-        }
-    })
-}
-
 enum GraphType {
     Un(UnGraph<Position, usize>),
     Di(DiGraph<Position, usize>),
@@ -243,62 +129,30 @@ impl GraphType {
         };
     }
 
-    fn find_edge(&self, a: NodeIndex, b: NodeIndex) -> Option<EdgeIndex> {
-        match self {
-            GraphType::Un(g) => g.find_edge(a, b),
-            GraphType::Di(g) => g.find_edge(a, b),
-        }
-    }
-
-    fn node_count(&self) -> usize {
-        match self {
-            GraphType::Un(g) => g.node_count(),
-            GraphType::Di(g) => g.node_count(),
-        }
-    }
-
-    fn longest_path(
-        &self,
-        min_intermediate: usize,
-        node_map: &NodeMap,
-        start: &Position,
-        goal: &Position,
-    ) -> usize {
+    /// The trails out of every junction as the junction they lead to and their length, by node
+    /// index
+    fn adjacency(&self) -> Adjacency {
         match self {
-            GraphType::Di(g) => all_simple_paths::<Vec<_>, _>(
-                g,
-                node_map[&start],
-                node_map[&goal],
-                min_intermediate,
-                None,
-            )
-            .map(|path| path_length(&path, self))
-            .max()
-            .unwrap(),
-            GraphType::Un(g) => all_simple_paths::<Vec<_>, _>(
-                g,
-                node_map[&start],
-                node_map[&goal],
-                min_intermediate,
-                None,
-            )
-            .map(|path| path_length(&path, self))
-            .max()
-            .unwrap(),
+            GraphType::Un(g) => adjacency(g),
+            GraphType::Di(g) => adjacency(g),
         }
     }
 
-    fn get_edge_distance(&self, eix: EdgeIndex) -> usize {
+    /// Junctions labelled with their position in the unpadded board, trails with their length
+    fn to_dot(&self) -> String {
+        let label = |_: NodeIndex, pos: &Position| format!("{},{}", pos.x - 1, pos.y - 1);
         match self {
-            GraphType::Un(g) => g[eix],
-            GraphType::Di(g) => g[eix],
+            GraphType::Un(g) => Dot::new(&g.map(label, |_, distance| *distance)).to_string(),
+            GraphType::Di(g) => Dot::new(&g.map(label, |_, distance| *distance)).to_string(),
         }
     }
 }
 
 type NodeMap = HashMap<Position, NodeIndex>;
 
-fn longest_path(start: Position, goal: Position, board: &Board, p2: bool) -> usize {
+/// The junctions reachable from `start` connected by the trails between them, undirected when
+/// the slopes can be climbed
+fn trail_graph(start: Position, board: &Board, p2: bool) -> (GraphType, NodeMap) {
     let mut visited: HashSet<Position> = HashSet::new();
     let mut node_map: NodeMap = HashMap::new();
     let mut g: GraphType = if p2 {
@@ -326,19 +180,105 @@ fn longest_path(start: Position, goal: Position, board: &Board, p2: bool) -> usi
         }
     }
 
-    // println!("{:?}", Dot::with_config(&g, &[]));
+    (g, node_map)
+}
 
-    let min_intermediate = if p2 { g.node_count() - 4 } else { 1 };
+type Adjacency = Vec<Vec<(usize, usize)>>;
+
+fn adjacency<Ty: EdgeType>(g: &Graph<Position, usize, Ty>) -> Adjacency {
+    g.node_indices()
+        .map(|node| {
+            g.edges(node)
+                .map(|edge| {
+                    let next = match edge.source() == node {
+                        true => edge.target(),
+                        false => edge.source(),
+                    };
+                    (next.index(), *edge.weight())
+                })
+                .collect()
+        })
+        .collect()
+}
 
-    g.longest_path(min_intermediate, &node_map, &start, &goal)
+/// The longest way from `node` to `goal` that avoids the `visited` junctions
+fn longest_walk(
+    adjacency: &Adjacency,
+    node: usize,
+    goal: usize,
+    visited: &mut [bool],
+) -> Option<usize> {
+    if node == goal {
+        return Some(0);
+    }
+    visited[node] = true;
+    let mut longest = None;
+    for &(next, length) in &adjacency[node] {
+        if !visited[next] {
+            if let Some(rest) = longest_walk(adjacency, next, goal, visited) {
+                longest = longest.max(Some(length + rest));
+            }
+        }
+    }
+    visited[node] = false;
+    longest
+}
+
+/// Enough of the ways out of `start` to spread the search over threads
+const BRANCHES: usize = 64;
+
+/// The first junctions of the ways from `start`, taken a junction further until there are
+/// [`BRANCHES`] of them, with their lengths so far
+fn branches(adjacency: &Adjacency, start: usize, goal: usize) -> Vec<(Vec<usize>, usize)> {
+    let mut branches = vec![(vec![start], 0)];
+    while branches.len() < BRANCHES {
+        let mut extended = false;
+        let mut next_branches = vec![];
+        for (path, length) in branches {
+            let last = *path.last().unwrap();
+            if last == goal {
+                next_branches.push((path, length));
+                continue;
+            }
+            for &(next, trail) in &adjacency[last] {
+                if !path.contains(&next) {
+                    next_branches.push(([&path[..], &[next]].concat(), length + trail));
+                    extended = true;
+                }
+            }
+        }
+        branches = next_branches;
+        if !extended {
+            break;
+        }
+    }
+    branches
+}
+
+fn longest_path(start: Position, goal: Position, board: &Board, p2: bool) -> usize {
+    let (g, node_map) = trail_graph(start, board, p2);
+    let adjacency = g.adjacency();
+    let (start, goal) = (node_map[&start].index(), node_map[&goal].index());
+
+    par_map(&branches(&adjacency, start, goal), |(path, length)| {
+        let mut visited = vec![false; adjacency.len()];
+        let (last, before) = path.split_last().unwrap();
+        for node in before {
+            visited[*node] = true;
+        }
+        longest_walk(&adjacency, *last, goal, &mut visited).map(|rest| length + rest)
+    })
+    .into_iter()
+    .flatten()
+    .max()
+    .unwrap()
 }
 
 fn remove_cells_with_one_neighbor(board: &Board, start: &Position, goal: &Position) -> Board {
     let mut board_out = board.clone();
-    for (ix, c) in board.indexed_iter() {
-        let pos = Position::new(ix.1, ix.0);
+    for (pos, c) in board.iter() {
         if *c == '.' && &pos != start && &pos != goal && valid_neighbors(pos, board).len() == 1 {
-            board_out[ix] = '#'
+            board_out[pos] = '#'
         }
     }
     board_out
@@ -354,42 +294,86 @@ fn remove_dead_ends(board: Board, start: &Position, goal: &Position) -> Board {
     next_board
 }
 
-fn parts(board: Board, p2: bool) -> usize {
-    let board = pad(&board, '#');
+/// The board with a wall around it and its dead ends filled in, plus the start and goal on it
+fn pruned_board(board: Board) -> (Board, Position, Position) {
+    let board = board.padded(1, '#');
 
     let start = Position::new(2, 1);
-    let goal = Position::new(board.dim().1 - 3, board.dim().0 - 2);
+    let goal = board.bottom_right() - Position::new(2, 1);
 
-    let board = remove_dead_ends(board, &start, &goal);
+    (remove_dead_ends(board, &start, &goal), start, goal)
+}
 
-    longest_path(start, goal, &board, p2)
+fn without_slopes(board: &Board) -> Board {
+    let mut board = board.clone();
+    board.cells_mut().map_inplace(|c| {
+        if ARROWS.contains(c) {
+            *c = '.'
+        }
+    });
+    board
 }
 
-fn part1(input: &str) -> usize {
-    let board = parse_board(input);
-    parts(board, false)
+fn parts(board: Board, p2: bool) -> usize {
+    let (board, start, goal) = pruned_board(board);
+    longest_path(start, goal, &board, p2)
 }
 
-fn part2(input: &str) -> usize {
-    let board = {
-        let mut board = parse_board(input);
-        board.map_inplace(|c| {
-            if ARROWS.contains(c) {
-                *c = '.'
-            }
-        });
-        board
-    };
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input<'a> = Board;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(&self, input: &str) -> Result<Board, ParseError> {
+        Ok(parse_grid(input, |c| "#.^>v<".contains(c).then_some(c))?)
+    }
 
-    parts(board, true)
+    fn part1(&self, board: &Board) -> usize {
+        parts(board.clone(), false)
+    }
+
+    fn part2(&self, board: &Board) -> Option<usize> {
+        Some(parts(without_slopes(board), true))
+    }
+
+    /// The junctions of part 2, where the slopes don't matter
+    fn dot(&self, board: &Board) -> Option<String> {
+        let (board, start, _) = pruned_board(without_slopes(board));
+        Some(trail_graph(start, &board, true).0.to_dot())
+    }
+
+    fn scene(&self, board: &Board) -> Option<Scene> {
+        let (pruned, start, goal) = pruned_board(board.clone());
+        let offset = Position::new(1, 1);
+        let dead_ends = board
+            .iter()
+            .filter(|&(pos, c)| *c != '#' && pruned[pos + offset] == '#')
+            .map(|(pos, _)| pos);
+        // The nodes of the graph the longest path is searched in
+        let junctions = pruned
+            .iter()
+            .filter(|&(pos, c)| {
+                *c != '#' && pos.neighbors4().filter(|next| pruned[*next] != '#').count() > 2
+            })
+            .map(|(pos, _)| pos)
+            .chain([start, goal])
+            .map(|pos| pos - offset);
+        Some(
+            Scene::new(board.cells().clone())
+                .layer("dead ends", Rgb::RED, Some('x'), dead_ends)
+                .layer("junctions", Rgb::YELLOW, Some('+'), junctions),
+        )
+    }
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(23)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub fn part1(input: &str) -> usize {
+    Day23.part1(&Day23.parse(input).unwrap())
+}
 
-    Ok(())
+pub fn part2(input: &str) -> usize {
+    Day23.part2(&Day23.parse(input).unwrap()).unwrap()
 }
 
 #[test]
@@ -421,18 +405,10 @@ fn example() {
     assert_eq!(part2(input), 154);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(23).unwrap();
-    assert_eq!(part1(input), 2154);
-    assert_eq!(part2(input), 6654);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(23).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(23, 1, part1(input));
+    assert_answer(23, 2, part2(input));
 }
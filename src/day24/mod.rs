@@ -1,15 +1,18 @@
+//! [Day 24: Never Tell Me The Odds](https://adventofcode.com/2023/day/24)
+
 extern crate nalgebra as na;
-extern crate test;
 
 use itertools::Itertools;
-use std::fmt;
-use std::num::ParseFloatError;
+use num_integer::Integer;
+use std::collections::HashSet;
 use std::str::FromStr;
 
-#[cfg(test)]
-use test::Bencher;
-
-use crate::utils::read_input_to_string;
+use crate::generate::Rng;
+use crate::parse::{number, parse_lines, split_once, ParseError};
+use crate::solution::Solution;
+use crate::utils::parallel::par_map;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 #[derive(Debug, Clone)]
 struct Vector2D {
@@ -17,16 +20,23 @@ struct Vector2D {
     y: f64,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 struct Vector3D {
     x: f64,
     y: f64,
     z: f64,
+    /// The coordinates as written, if they are all whole numbers
+    exact: Option<Exact>,
 }
 
+/// Integer coordinates, all the input has, for part 2 where f64 products of 15 digit numbers
+/// would round
+type Exact = na::Vector3<i128>;
+
 impl Vector3D {
-    fn new(x: f64, y: f64, z: f64) -> Self {
-        Self { x, y, z }
+    fn exact(&self) -> Option<Exact> {
+        self.exact
     }
 }
 
@@ -36,45 +46,22 @@ struct Ray {
     direction: Vector3D,
 }
 
-#[derive(Debug)]
-enum ParseError {
-    InvalidFormat,
-    FloatError(ParseFloatError),
-}
-
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            ParseError::InvalidFormat => write!(f, "Invalid input format"),
-            ParseError::FloatError(ref err) => err.fmt(f),
-        }
-    }
-}
-
-impl From<ParseFloatError> for ParseError {
-    fn from(err: ParseFloatError) -> Self {
-        ParseError::FloatError(err)
-    }
-}
-
 impl FromStr for Vector3D {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<f64> = s
-            .split(',')
-            .map(str::trim)
-            .map(str::parse)
-            .collect::<Result<_, _>>()?;
+        let parts: Vec<f64> = s.split(',').map(number).collect::<Result<_, _>>()?;
+        let exact: Option<Vec<i128>> = s.split(',').map(|c| c.trim().parse().ok()).collect();
 
-        if parts.len() == 3 {
+        if let [x, y, z] = parts[..] {
             Ok(Vector3D {
-                x: parts[0],
-                y: parts[1],
-                z: parts[2],
+                x,
+                y,
+                z,
+                exact: exact.map(Exact::from_vec),
             })
         } else {
-            Err(ParseError::InvalidFormat)
+            Err(ParseError::format("[x], [y], [z]", s.trim()))
         }
     }
 }
@@ -83,15 +70,11 @@ impl FromStr for Ray {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split('@').map(str::trim).collect();
-
-        if parts.len() == 2 {
-            let origin = parts[0].parse()?;
-            let direction = parts[1].parse()?;
-            Ok(Ray { origin, direction })
-        } else {
-            Err(ParseError::InvalidFormat)
-        }
+        let (origin, direction) = split_once(s, "@", "[position] @ [velocity]")?;
+        Ok(Ray {
+            origin: origin.parse()?,
+            direction: direction.parse()?,
+        })
     }
 }
 
@@ -121,10 +104,39 @@ impl Ray {
         None
     }
 
+    /// `xy_intersection` by Cramer's rule in integers, so nearly parallel paths and crossings on
+    /// the edge of the test area come out right. `None` for parallel paths, paths crossing in the
+    /// past and fractional coordinates
+    fn exact_xy_crossing(&self, other: &Ray) -> Option<Crossing> {
+        let (origin, direction) = (self.origin.exact()?, self.direction.exact()?);
+        let (other_origin, other_direction) = (other.origin.exact()?, other.direction.exact()?);
+        let determinant = other_direction.x * direction.y - direction.x * other_direction.y;
+        if determinant == 0 {
+            return None;
+        }
+
+        let offset = other_origin - origin;
+        let sign = determinant.signum();
+        let times = (
+            sign * (other_direction.x * offset.y - offset.x * other_direction.y),
+            sign * (direction.x * offset.y - direction.y * offset.x),
+        );
+        if times.0 < 0 || times.1 < 0 {
+            return None;
+        }
+        let denominator = determinant.abs();
+        Some(Crossing {
+            times,
+            point: (
+                origin.x * denominator + direction.x * times.0,
+                origin.y * denominator + direction.y * times.0,
+            ),
+            denominator,
+        })
+    }
+
     fn xy_intersects(&self, other: &Ray, bounds: &(Vector2D, Vector2D)) -> bool {
         if let Some(intersection) = self.xy_intersection(other) {
-            // println!("{self:?} {other:?} intersect at {intersection:?}");
-
             intersection.x >= bounds.0.x
                 && intersection.x <= bounds.1.x
                 && intersection.y >= bounds.0.y
@@ -135,15 +147,34 @@ impl Ray {
     }
 }
 
+/// Where two paths cross in x and y: the time of each and the point, as fractions over
+/// `denominator`, which is positive
+#[derive(Debug, PartialEq)]
+struct Crossing {
+    times: (i128, i128),
+    point: (i128, i128),
+    denominator: i128,
+}
+
+impl Crossing {
+    /// Whether the point is in the square from `low` to `high`, edges included
+    fn within(&self, (low, high): (i128, i128)) -> bool {
+        let range = low * self.denominator..=high * self.denominator;
+        range.contains(&self.point.0) && range.contains(&self.point.1)
+    }
+}
+
+#[allow(dead_code)]
 const EXAMPLE_BOUND: (f64, f64) = (7.0, 27.0);
-const TASK_BOUND: (f64, f64) = (200000000000000.0, 400000000000000.0);
+pub const TASK_BOUND: (f64, f64) = (200000000000000.0, 400000000000000.0);
 
-fn part1(input: &str, bound: (f64, f64)) -> usize {
-    let hailstones: Vec<Ray> = input
-        .lines()
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .unwrap();
+/// Counted exactly for whole numbers, like the puzzle's, in floats otherwise
+fn xy_crossings(hailstones: &[Ray], bound: (f64, f64)) -> usize {
+    let exact = (bound.0.fract() == 0.0 && bound.1.fract() == 0.0)
+        && hailstones
+            .iter()
+            .all(|ray| ray.origin.exact().is_some() && ray.direction.exact().is_some());
+    let exact_bound = (bound.0 as i128, bound.1 as i128);
     let bounds = (
         Vector2D {
             x: bound.0,
@@ -155,19 +186,277 @@ fn part1(input: &str, bound: (f64, f64)) -> usize {
         },
     );
 
-    hailstones
+    let indices = (0..hailstones.len()).collect_vec();
+    par_map(&indices, |&i| {
+        hailstones[i + 1..]
+            .iter()
+            .filter(|other| match exact {
+                true => hailstones[i]
+                    .exact_xy_crossing(other)
+                    .is_some_and(|crossing| crossing.within(exact_bound)),
+                false => hailstones[i].xy_intersects(other, &bounds),
+            })
+            .count()
+    })
+    .into_iter()
+    .sum()
+}
+
+/// A position and a velocity
+type Path = (Exact, Exact);
+
+/// The same direction with the common factor divided out
+fn reduce(direction: Exact) -> Exact {
+    let gcd = direction.iter().fold(0, |gcd: i128, c| gcd.gcd(c));
+    match gcd {
+        0 => direction,
+        _ => direction.map(|c| c / gcd),
+    }
+}
+
+/// When a line through the origin along `direction` meets the hailstone, if it does at a whole time
+fn meeting_time((position, velocity): &Path, direction: &Exact) -> Option<i128> {
+    // position + t * velocity is parallel to direction
+    let (offset, closing) = (
+        checked_cross(position, direction)?,
+        checked_cross(velocity, direction)?,
+    );
+    let k = (0..3).find(|&k| closing[k] != 0)?;
+    if offset[k].checked_rem(closing[k])? != 0 {
+        return None;
+    }
+    offset[k].checked_neg()?.checked_div(closing[k])
+}
+
+/// `a - b`, `None` if it doesn't fit in an i128
+fn checked_sub(a: &Exact, b: &Exact) -> Option<Exact> {
+    let mut difference = Exact::zeros();
+    for k in 0..3 {
+        difference[k] = a[k].checked_sub(b[k])?;
+    }
+    Some(difference)
+}
+
+/// `a × b`, `None` if it doesn't fit in an i128
+fn checked_cross(a: &Exact, b: &Exact) -> Option<Exact> {
+    let term = |i: usize, j: usize| a[i].checked_mul(b[j])?.checked_sub(a[j].checked_mul(b[i])?);
+    Some(Exact::new(term(1, 2)?, term(2, 0)?, term(0, 1)?))
+}
+
+/// `position + velocity * time`, `None` if it doesn't fit in an i128
+fn checked_at((position, velocity): &Path, time: i128) -> Option<Exact> {
+    let mut at = Exact::zeros();
+    for k in 0..3 {
+        at[k] = velocity[k].checked_mul(time)?.checked_add(position[k])?;
+    }
+    Some(at)
+}
+
+/// A rock thrown through the hailstones `[reference, a, b]`. Seen from `reference` the rock's line
+/// goes through the origin and crosses the paths of `a` and `b`, so it lies in both planes through
+/// the origin and those paths. The times it meets `a` and `b` then give its velocity and origin.
+/// `None` if the hailstones don't determine a rock, or the numbers don't fit in an i128
+fn throw(hailstones: &[Path], [reference, a, b]: [usize; 3]) -> Option<Path> {
+    let (origin, velocity) = hailstones[reference];
+    let relative = |i: usize| {
+        let (position, hailstone_velocity) = &hailstones[i];
+        Some((
+            checked_sub(position, &origin)?,
+            checked_sub(hailstone_velocity, &velocity)?,
+        ))
+    };
+    let (a_relative, b_relative) = (relative(a)?, relative(b)?);
+    let normal = |(position, velocity): Path| checked_cross(&position, &velocity);
+    let direction = reduce(checked_cross(&normal(a_relative)?, &normal(b_relative)?)?);
+    if direction == Exact::zeros() {
+        return None;
+    }
+
+    let (time_a, time_b) = (
+        meeting_time(&a_relative, &direction)?,
+        meeting_time(&b_relative, &direction)?,
+    );
+    if time_a == time_b {
+        return None;
+    }
+    let (hit_a, hit_b) = (
+        checked_at(&hailstones[a], time_a)?,
+        checked_at(&hailstones[b], time_b)?,
+    );
+    let travelled = checked_sub(&hit_b, &hit_a)?;
+    let elapsed = time_b.checked_sub(time_a)?;
+    let mut rock_velocity = Exact::zeros();
+    for k in 0..3 {
+        if travelled[k].checked_rem(elapsed)? != 0 {
+            return None;
+        }
+        rock_velocity[k] = travelled[k].checked_div(elapsed)?;
+    }
+    let rock = checked_at(&(hit_a, rock_velocity), time_a.checked_neg()?)?;
+    Some((rock, rock_velocity))
+}
+
+/// Whether the rock and the hailstone are at the same place at some time from 0 on
+fn hits((rock, rock_velocity): &Path, (hailstone, velocity): &Path) -> bool {
+    let (Some(offset), Some(closing)) = (
+        checked_sub(hailstone, rock),
+        checked_sub(rock_velocity, velocity),
+    ) else {
+        return false;
+    };
+    if closing == Exact::zeros() {
+        return offset == Exact::zeros();
+    }
+    let dot = (0..3).try_fold(0_i128, |dot, k| {
+        dot.checked_add(offset[k].checked_mul(closing[k])?)
+    });
+    checked_cross(&offset, &closing) == Some(Exact::zeros()) && dot.is_some_and(|dot| dot >= 0)
+}
+
+/// The rock's origin and velocity from the first hailstones that determine it, checked against
+/// every hailstone
+fn rock_throw(hailstones: &[Ray]) -> Option<Path> {
+    let hailstones: Vec<Path> = hailstones
         .iter()
+        .map(|ray| Some((ray.origin.exact()?, ray.direction.exact()?)))
+        .collect::<Option<_>>()?;
+    (0..hailstones.len())
         .tuple_combinations()
-        .filter(|(a, b)| a.xy_intersects(b, &bounds))
-        .count()
+        .filter_map(|(reference, a, b)| throw(&hailstones, [reference, a, b]))
+        .find(|rock| hailstones.iter().all(|hailstone| hits(rock, hailstone)))
+}
+
+pub struct Hailstones(Vec<Ray>);
+
+/// `bound` is the test area for part 1, the same for x and y
+pub struct Day24 {
+    pub bound: (f64, f64),
+}
+
+impl Solution for Day24 {
+    type Input<'a> = Hailstones;
+    type Answer1 = usize;
+    type Answer2 = i128;
+
+    fn parse(&self, input: &str) -> Result<Hailstones, ParseError> {
+        Ok(Hailstones(parse_lines(input, str::parse)?))
+    }
+
+    fn part1(&self, Hailstones(hailstones): &Hailstones) -> usize {
+        xy_crossings(hailstones, self.bound)
+    }
+
+    /// The sum of the coordinates the rock is thrown from, `None` if no rock hits every hailstone
+    fn part2(&self, Hailstones(hailstones): &Hailstones) -> Option<i128> {
+        rock_throw(hailstones).map(|(origin, _)| origin.sum())
+    }
+}
+
+pub fn part1(input: &str, bound: (f64, f64)) -> usize {
+    let day = Day24 { bound };
+    day.part1(&day.parse(input).unwrap())
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(24)?;
-    dbg!(part1(input, TASK_BOUND),);
-    // dbg!(part2(input));
+pub fn part2(input: &str) -> i128 {
+    let day = Day24 { bound: TASK_BOUND };
+    day.part2(&day.parse(input).unwrap()).unwrap()
+}
+
+/// `size` hailstones and a rock thrown so that it hits every one of them, each at a different time
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    let rock = [(); 3].map(|_| rng.range(200_000_000_000_000..400_000_000_000_000));
+    let rock_velocity = [(); 3].map(|_| rng.range(-250..250));
+    let mut times = HashSet::new();
+    (0..size)
+        .map(|_| {
+            let time = loop {
+                let time = rng.range(100_000_000_000..1_000_000_000_000);
+                if times.insert(time) {
+                    break time;
+                }
+            };
+            let velocity = rock_velocity.map(|rock_velocity| loop {
+                let velocity = rng.range(-400..400);
+                if velocity != rock_velocity {
+                    break velocity;
+                }
+            });
+            let position = [0, 1, 2].map(|i| rock[i] + time * (rock_velocity[i] - velocity[i]));
+            format!(
+                "{} @ {}",
+                position.iter().join(", "),
+                velocity.iter().join(", ")
+            )
+        })
+        .join("\n")
+}
+
+#[test]
+fn generated() {
+    let input = generate(50, &mut Rng::new(24));
+    let day = Day24 { bound: TASK_BOUND };
+    let Hailstones(hailstones) = day.parse(&input).unwrap();
+    assert_eq!(hailstones.len(), 50);
+
+    let rng = &mut Rng::new(24);
+    let rock = [(); 3].map(|_| rng.range(200_000_000_000_000..400_000_000_000_000) as i128);
+    let (origin, velocity) = rock_throw(&hailstones).unwrap();
+    assert_eq!(origin, Exact::from(rock));
+    for ray in &hailstones {
+        let (position, hailstone_velocity) =
+            (ray.origin.exact().unwrap(), ray.direction.exact().unwrap());
+        let (offset, closing) = (position - origin, velocity - hailstone_velocity);
+        let time = offset.x / closing.x;
+        assert!(time >= 0);
+        assert_eq!(offset, closing * time, "{ray:?}");
+    }
+}
+
+#[test]
+fn exact_matches_float() {
+    let day = Day24 { bound: TASK_BOUND };
+    let Hailstones(hailstones) = day.parse(&generate(100, &mut Rng::new(23))).unwrap();
+    for (a, b) in hailstones.iter().tuple_combinations() {
+        match (a.exact_xy_crossing(b), a.xy_intersection(b)) {
+            (Some(exact), Some(float)) => {
+                let x = exact.point.0 as f64 / exact.denominator as f64;
+                let y = exact.point.1 as f64 / exact.denominator as f64;
+                assert!((x - float.x).abs() <= x.abs() * 1e-9, "{x} {float:?}");
+                assert!((y - float.y).abs() <= y.abs() * 1e-9, "{y} {float:?}");
+            }
+            (exact, float) => assert_eq!(exact.is_some(), float.is_some(), "{a:?} {b:?}"),
+        }
+    }
+}
+
+#[test]
+fn exact_edges() {
+    // Nearly parallel paths crossing on the corner of the test area, or just outside it
+    let corner = 200_000_000_000_000_i128;
+    let ray = |x: i128, vx: i128, vy: i128, time: i128| {
+        format!(
+            "{}, {}, 0 @ {vx}, {vy}, 0",
+            x - vx * time,
+            corner - vy * time
+        )
+        .parse::<Ray>()
+        .unwrap()
+    };
+    let bound = (corner, 2 * corner);
+    let a = ray(corner, 999, 1000, 10);
+    let crossing = a.exact_xy_crossing(&ray(corner, 1000, 1001, 20)).unwrap();
+    assert_eq!(
+        crossing.times,
+        (10 * crossing.denominator, 20 * crossing.denominator)
+    );
+    assert!(crossing.within(bound));
+    assert_eq!(a.exact_xy_crossing(&ray(corner, 1000, 1001, -20)), None);
 
-    Ok(())
+    let (a, b) = (
+        ray(corner - 1, 999, 1000, 10),
+        ray(corner - 1, 1000, 1001, 20),
+    );
+    assert!(!a.exact_xy_crossing(&b).unwrap().within(bound));
 }
 
 #[test]
@@ -178,21 +467,32 @@ fn example() {
 12, 31, 28 @ -1, -2, -1
 20, 19, 15 @  1, -5, -3";
     assert_eq!(part1(input, EXAMPLE_BOUND), 2);
-    // assert_eq!(part2(input), 47);
+    assert_eq!(part2(input), 47);
+
+    // A fast hailstone the rock meets at time 5 makes the cross products of every triple with it
+    // overflow, the others still find the rock
+    let fast = "5000000000009, -4999999999982, 15000000000020 @ -1000000000000, 1000000000000, -3000000000000";
+    assert_eq!(part2(&format!("{fast}\n{input}")), 47);
+
+    // Past 2^53, where an f64 would round the coordinates
+    let shift = (1_i128 << 55) + 1;
+    let shifted = input
+        .lines()
+        .map(|line| {
+            let (position, velocity) = line.split_once(" @ ").unwrap();
+            let position = position
+                .split(',')
+                .map(|c| c.trim().parse::<i128>().unwrap() + shift);
+            format!("{} @ {velocity}", position.format(", "))
+        })
+        .join("\n");
+    assert_eq!(part2(&shifted), 47 + 3 * shift);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(24).unwrap();
-    assert_eq!(part1(input, TASK_BOUND), 13892);
-    // assert_eq!(part2(input), 1);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(24).unwrap();
-        part1(input, TASK_BOUND);
-        // part2(input);
-    })
+    assert_answer(24, 1, part1(input, TASK_BOUND));
+    assert_answer(24, 2, part2(input));
 }
@@ -1,60 +1,57 @@
-extern crate test;
+//! [Day 25: Snowverload](https://adventofcode.com/2023/day/25)
 
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use petgraph::dot::{Config, Dot};
-use petgraph::graph::{NodeIndex, UnGraph};
-use petgraph::prelude::Dfs;
+use petgraph::graph::{EdgeReference, NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
 use petgraph::Graph;
-#[cfg(test)]
-use test::Bencher;
+use priority_queue::PriorityQueue;
 
-use crate::utils::read_input_to_string;
+use crate::generate::Rng;
+use crate::parse::{parse_lines, split_once, ParseError, ParseResult};
+use crate::solution::{Solution, Unsolved};
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 type Component = [char; 3];
 
-fn parse_line(line: &str) -> Result<(Component, Vec<Component>), &'static str> {
-    let parts: Vec<&str> = line.split(':').map(str::trim).collect();
-    if parts.len() != 2 {
-        return Err("Invalid line format");
-    }
+fn parse_line(line: &str) -> ParseResult<(Component, Vec<Component>)> {
+    let (key, values) = split_once(line, ":", "[component]: [components]")?;
 
-    let key = to_component(parts[0])?;
-    let values = parts[1]
+    let key = to_component(key.trim())?;
+    let values = values
         .split_whitespace()
         .map(to_component)
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<ParseResult<Vec<_>>>()?;
 
     Ok((key, values))
 }
 
-fn to_component(s: &str) -> Result<Component, &'static str> {
-    let mut chars = s.chars();
-    let c1 = chars.next().ok_or("String is too short")?;
-    let c2 = chars.next().ok_or("String is too short")?;
-    let c3 = chars.next().ok_or("String is too short")?;
-
-    if chars.next().is_some() {
-        return Err("String is too long");
+fn to_component(s: &str) -> ParseResult<Component> {
+    match s.chars().collect::<Vec<_>>()[..] {
+        [c1, c2, c3] => Ok([c1, c2, c3]),
+        _ => Err(ParseError::format("a component of three characters", s)),
     }
-
-    Ok([c1, c2, c3])
 }
 
+/// Every component with the components it is wired to
+type Connections = Vec<(Component, Vec<Component>)>;
+
 type ComponentGraph = UnGraph<String, bool>;
 
 fn build_graph(
-    component_connections: Vec<(Component, Vec<Component>)>,
+    component_connections: &Connections,
 ) -> (ComponentGraph, HashMap<Component, NodeIndex>) {
     let mut g: ComponentGraph = Graph::new_undirected();
     let mut component_map: HashMap<Component, NodeIndex> = HashMap::new();
 
-    for (source, destinations) in component_connections {
+    for &(source, ref destinations) in component_connections {
         component_map
             .entry(source)
             .or_insert_with(|| g.add_node(source.iter().collect::<String>()));
-        for dest in destinations {
+        for &dest in destinations {
             component_map
                 .entry(dest)
                 .or_insert_with(|| g.add_node(dest.iter().collect::<String>()));
@@ -62,66 +59,201 @@ fn build_graph(
         }
     }
 
-    // println!("{:?}", Dot::with_config(&g, &[Config::EdgeNoLabel]));
-
     (g, component_map)
 }
 
-fn count_nodes_in_subgraphs(graph: ComponentGraph, test_nodes: Vec<NodeIndex>) -> Vec<usize> {
-    let mut visited = HashSet::new();
-    let mut counts = Vec::new();
+/// The groups of components left after cutting the fewest wires, and those wires
+#[derive(Debug, PartialEq)]
+pub struct Cut {
+    pub sizes: [usize; 2],
+    pub wires: Vec<(String, String)>,
+}
 
-    for node in test_nodes {
-        if !visited.contains(&node) {
-            let mut dfs = Dfs::new(&graph, node);
-            let mut count = 0;
+/// Stoer–Wagner: every phase adds the component most tightly wired to the ones added so far, the
+/// wires of the last one added are a cut, then it's merged into the one before it
+fn minimum_cut(g: &ComponentGraph) -> Cut {
+    let mut weights: Vec<HashMap<usize, usize>> = vec![HashMap::new(); g.node_count()];
+    for edge in g.edge_references() {
+        let (a, b) = (edge.source().index(), edge.target().index());
+        *weights[a].entry(b).or_default() += 1;
+        *weights[b].entry(a).or_default() += 1;
+    }
+    let mut merged: Vec<Vec<usize>> = (0..g.node_count()).map(|i| vec![i]).collect();
+    let mut active = (0..g.node_count()).collect_vec();
+    let mut best: Option<(usize, Vec<usize>)> = None;
 
-            while let Some(nx) = dfs.next(&graph) {
-                if visited.insert(nx) {
-                    count += 1;
-                }
+    while active.len() > 1 {
+        let mut queue: PriorityQueue<usize, usize> = active.iter().map(|&i| (i, 0)).collect();
+        let mut order = vec![];
+        let mut cut_weight = 0;
+        while let Some((i, weight)) = queue.pop() {
+            order.push(i);
+            cut_weight = weight;
+            for (j, w) in &weights[i] {
+                queue.change_priority_by(j, |priority| *priority += w);
             }
+        }
 
-            counts.push(count);
+        let (s, t) = (order[order.len() - 2], order[order.len() - 1]);
+        if best.as_ref().is_none_or(|(weight, _)| cut_weight < *weight) {
+            best = Some((cut_weight, merged[t].clone()));
         }
+        let t_merged = std::mem::take(&mut merged[t]);
+        merged[s].extend(t_merged);
+        for (j, w) in std::mem::take(&mut weights[t]) {
+            weights[j].remove(&t);
+            if j != s {
+                *weights[s].entry(j).or_default() += w;
+                *weights[j].entry(s).or_default() += w;
+            }
+        }
+        active.retain(|&i| i != t);
     }
 
-    counts
+    let side: HashSet<usize> = best
+        .map(|(_, side)| side)
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    let wires = g
+        .edge_references()
+        .filter(|edge| {
+            side.contains(&edge.source().index()) != side.contains(&edge.target().index())
+        })
+        .map(|edge| {
+            let (a, b) = (&g[edge.source()], &g[edge.target()]);
+            (a.min(b).clone(), a.max(b).clone())
+        })
+        .sorted()
+        .collect();
+    Cut {
+        sizes: [side.len(), g.node_count() - side.len()],
+        wires,
+    }
 }
 
-fn part1(input: &str, separation_nodes: [(&str, &str); 3]) -> usize {
-    let component_connections = input
-        .lines()
-        .map(parse_line)
-        .collect::<Result<_, _>>()
-        .unwrap();
-    let (mut g, component_map) = build_graph(component_connections);
-
-    for (a, b) in separation_nodes {
-        match (to_component(a), to_component(b)) {
-            (Ok(a), Ok(b)) => {
-                g.remove_edge(g.find_edge(component_map[&a], component_map[&b]).unwrap())
-            }
-            _ => panic!("wut"),
+pub fn part1(input: &str) -> usize {
+    Day25.part1(&Day25.parse(input).unwrap())
+}
+
+/// The fewest wires to cut to split the components into two groups, with the sizes of the
+/// groups. Part 1 is the product of the sizes
+pub fn cut(input: &str) -> Result<Cut, ParseError> {
+    let (g, _) = build_graph(&Day25.parse(input)?);
+    Ok(minimum_cut(&g))
+}
+
+/// Finds the wires to cut itself, see `minimum_cut`
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input<'a> = Connections;
+    type Answer1 = usize;
+    type Answer2 = Unsolved;
+
+    fn parse(&self, input: &str) -> Result<Connections, ParseError> {
+        parse_lines(input, parse_line)
+    }
+
+    fn part1(&self, connections: &Connections) -> usize {
+        let (g, _) = build_graph(connections);
+        minimum_cut(&g).sizes.iter().product()
+    }
+
+    /// The components and their wires, with the wires to cut in red
+    fn dot(&self, connections: &Connections) -> Option<String> {
+        let (g, _) = build_graph(connections);
+        let Cut { wires, .. } = minimum_cut(&g);
+        let is_cut = |a: &String, b: &String| {
+            wires
+                .iter()
+                .any(|(x, y)| (x, y) == (a, b) || (y, x) == (a, b))
         };
+        let edge_attributes = |g: &ComponentGraph, edge: EdgeReference<bool>| match is_cut(
+            &g[edge.source()],
+            &g[edge.target()],
+        ) {
+            true => "color = red".to_string(),
+            false => String::new(),
+        };
+        Some(
+            Dot::with_attr_getters(&g, &[Config::EdgeNoLabel], &edge_attributes, &|_, _| {
+                String::new()
+            })
+            .to_string(),
+        )
+    }
+}
+
+/// `size` components in two groups with 3 wires between them, and that cut. A wire connects each
+/// component to the next two of its group, so splitting a group takes at least 4 cuts, and some
+/// more are random
+fn planted_cut(size: usize, rng: &mut Rng) -> (String, Cut) {
+    assert!(size >= 10, "a planted cut needs at least 10 components");
+    let names = rng.names(size, 3, &[]);
+    let first = (rng.range(size as i64 / 3..size as i64 * 2 / 3 + 1) as usize).clamp(5, size - 5);
+    let groups = [0..first, first..size];
+    let mut wires = BTreeSet::new();
+    let mut wire = |a: usize, b: usize| {
+        if a != b {
+            wires.insert((a.min(b), a.max(b)));
+        }
+    };
+    for group in &groups {
+        for i in group.clone() {
+            let next = |step| group.start + (i - group.start + step) % group.len();
+            wire(i, next(1));
+            wire(i, next(2));
+            wire(i, group.start + rng.below(group.len()));
+        }
+    }
+
+    let ends = groups.clone().map(|group| {
+        let mut group = group.collect_vec();
+        rng.shuffle(&mut group);
+        group
+    });
+    let cut = (0..3).map(|i| (ends[0][i], ends[1][i])).collect_vec();
+    wires.extend(cut.iter().copied());
+
+    let mut connections: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (a, b) in wires {
+        let (a, b) = if rng.below(2) == 0 { (a, b) } else { (b, a) };
+        connections.entry(a).or_default().push(b);
     }
+    let mut lines = connections
+        .into_iter()
+        .map(|(a, bs)| format!("{}: {}", names[a], bs.iter().map(|b| &names[*b]).join(" ")))
+        .collect_vec();
+    rng.shuffle(&mut lines);
 
-    let separated_nodes = [separation_nodes[0].0, separation_nodes[0].1];
-    let nodes = separated_nodes
+    let wires = cut
         .iter()
-        .map(|s| component_map[&to_component(s).unwrap()])
+        .map(|&(a, b)| {
+            let (a, b) = (&names[a], &names[b]);
+            (a.min(b).clone(), a.max(b).clone())
+        })
+        .sorted()
         .collect();
-    count_nodes_in_subgraphs(g, nodes).into_iter().product()
+    let sizes = [first, size - first];
+    (lines.join("\n"), Cut { sizes, wires })
 }
 
-// Found using sfdp layout in Graphviz:  sfdp -Tsvg aoc-2023/src/day25/graph.dot -o aoc-2023/src/day25/graph.svg
-const SEPARATION_NODES: [(&str, &str); 3] = [("nvf", "bvz"), ("cbl", "vmq"), ("klk", "xgz")];
-
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(25)?;
-    dbg!(part1(input, SEPARATION_NODES));
+/// `size` components, at least 10, wired so that cutting 3 wires splits them into two groups
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    planted_cut(size, rng).0
+}
 
-    Ok(())
+#[test]
+fn generated() {
+    let (input, planted) = planted_cut(40, &mut Rng::new(25));
+    assert_eq!(generate(40, &mut Rng::new(25)), input);
+    let found = cut(&input).unwrap();
+    assert_eq!(found.wires, planted.wires);
+    assert_eq!(
+        found.sizes.iter().sorted().collect_vec(),
+        planted.sizes.iter().sorted().collect_vec()
+    );
 }
 
 #[test]
@@ -139,22 +271,18 @@ nvd: lhk
 lsr: lhk
 rzs: qnr cmg lsr rsh
 frs: qnr lhk lsr";
+    assert_eq!(part1(input), 54);
+
     assert_eq!(
-        part1(input, [("cmg", "bvb"), ("nvd", "jqt"), ("hfx", "pzl")]),
-        54
+        cut(input).unwrap().wires,
+        [("bvb", "cmg"), ("hfx", "pzl"), ("jqt", "nvd")]
+            .map(|(a, b)| (a.to_string(), b.to_string()))
     );
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(25).unwrap();
-    assert_eq!(part1(input, SEPARATION_NODES), 583632);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(25).unwrap();
-        part1(input, SEPARATION_NODES);
-    })
+    assert_answer(25, 1, part1(input));
 }
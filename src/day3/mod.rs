@@ -1,14 +1,14 @@
-extern crate test;
+//! [Day 3: Gear Ratios](https://adventofcode.com/2023/day/3)
 
 use std::collections::HashMap;
 use std::hash::Hash;
 
-#[cfg(test)]
-use test::Bencher;
+use crate::parse::{number, ParseError, ParseResult};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
-use crate::utils::read_input_to_string;
-
-#[derive(Eq, Hash, PartialEq, Debug)]
+#[derive(Eq, Hash, PartialEq, Debug, Clone)]
 
 struct Point {
     x: i32,
@@ -31,24 +31,30 @@ struct EnginePart {
 }
 
 impl EnginePart {
-    fn new(digits: &str, end_x: i32, y: i32) -> Self {
-        let number: u32 = digits.parse().unwrap();
+    fn new(digits: &str, end_x: i32, y: i32) -> ParseResult<Self> {
+        let number: u32 = number(digits)?;
         let start_x = end_x - number.checked_ilog10().unwrap_or(0) as i32;
-        EnginePart {
+        Ok(EnginePart {
             number,
             start_x,
             end_x,
             y,
-        }
+        })
     }
 }
 
-fn store_part(parts: &mut HashMap<Point, EnginePart>, buffer: &mut String, x_pos: i32, y_pos: i32) {
+fn store_part(
+    parts: &mut HashMap<Point, EnginePart>,
+    buffer: &mut String,
+    x_pos: i32,
+    y_pos: i32,
+) -> ParseResult<()> {
     if !buffer.is_empty() {
-        let part = EnginePart::new(buffer, x_pos, y_pos);
+        let part = EnginePart::new(buffer, x_pos, y_pos)?;
         parts.insert(Point::new(x_pos, y_pos), part);
         buffer.clear();
     };
+    Ok(())
 }
 
 fn symbol_adjacent(part: &EnginePart, symbols: &HashMap<Point, char>) -> bool {
@@ -79,18 +85,23 @@ fn update_adjacencies(
     }
 }
 
-fn parse_schematic(input: &str) -> (HashMap<Point, EnginePart>, HashMap<Point, char>) {
+type Parts = HashMap<Point, EnginePart>;
+
+fn parse_schematic(input: &str) -> ParseResult<(Parts, HashMap<Point, char>)> {
     let mut parts: HashMap<Point, EnginePart> = HashMap::new();
     let mut symbols: HashMap<Point, char> = HashMap::new();
     let mut buffer: String = String::new();
 
     for (y_pos, line) in input.lines().enumerate() {
+        let store = |parts: &mut Parts, buffer: &mut String, x_pos| {
+            store_part(parts, buffer, x_pos, y_pos as i32).map_err(|err| err.at_line(y_pos + 1))
+        };
         for (x_pos, c) in line.chars().enumerate() {
             match c {
-                '.' => store_part(&mut parts, &mut buffer, x_pos as i32 - 1, y_pos as i32),
+                '.' => store(&mut parts, &mut buffer, x_pos as i32 - 1)?,
                 '0'..='9' => buffer.push(c),
                 _ => {
-                    store_part(&mut parts, &mut buffer, x_pos as i32 - 1, y_pos as i32);
+                    store(&mut parts, &mut buffer, x_pos as i32 - 1)?;
                     symbols.insert(
                         Point {
                             x: x_pos as i32,
@@ -101,57 +112,74 @@ fn parse_schematic(input: &str) -> (HashMap<Point, EnginePart>, HashMap<Point, c
                 }
             }
         }
-        store_part(&mut parts, &mut buffer, line.len() as i32 - 1, y_pos as i32)
+        store(&mut parts, &mut buffer, line.len() as i32 - 1)?;
     }
-    (parts, symbols)
+    Ok((parts, symbols))
 }
 
-fn part1(input: &str) -> u32 {
-    let (parts, symbols) = parse_schematic(input);
-
-    parts
-        .values()
-        .filter_map(|part| {
-            if symbol_adjacent(part, &symbols) {
-                Some(part.number)
-            } else {
-                None
-            }
-        })
-        .sum()
+pub struct Schematic {
+    parts: HashMap<Point, EnginePart>,
+    symbols: HashMap<Point, char>,
 }
 
-fn part2(input: &str) -> u32 {
-    let (parts, mut symbols) = parse_schematic(input);
-    symbols.retain(|_, c| *c == '*');
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input<'a> = Schematic;
+    type Answer1 = u32;
+    type Answer2 = u32;
 
-    let mut gears: HashMap<Point, Vec<u32>> = HashMap::new();
-    for part in parts.values() {
-        update_adjacencies(part, &symbols, &mut gears);
+    fn parse(&self, input: &str) -> Result<Schematic, ParseError> {
+        let (parts, symbols) = parse_schematic(input)?;
+        Ok(Schematic { parts, symbols })
+    }
+
+    fn part1(&self, Schematic { parts, symbols }: &Schematic) -> u32 {
+        parts
+            .values()
+            .filter_map(|part| {
+                if symbol_adjacent(part, symbols) {
+                    Some(part.number)
+                } else {
+                    None
+                }
+            })
+            .sum()
+    }
+
+    fn part2(&self, Schematic { parts, symbols }: &Schematic) -> Option<u32> {
+        let mut symbols = symbols.clone();
+        symbols.retain(|_, c| *c == '*');
+
+        let mut gears: HashMap<Point, Vec<u32>> = HashMap::new();
+        for part in parts.values() {
+            update_adjacencies(part, &symbols, &mut gears);
+        }
+        let gear_ratios = gears
+            .values()
+            .filter_map(|gear_values| {
+                if gear_values.len() == 2 {
+                    Some(gear_values.iter().product::<u32>())
+                } else {
+                    None
+                }
+            })
+            .sum();
+        Some(gear_ratios)
     }
-    gears
-        .values()
-        .filter_map(|gear_values| {
-            if gear_values.len() == 2 {
-                Some(gear_values.iter().product::<u32>())
-            } else {
-                None
-            }
-        })
-        .sum()
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(3)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub fn part1(input: &str) -> u32 {
+    Day3.part1(&Day3.parse(input).unwrap())
+}
 
-    Ok(())
+pub fn part2(input: &str) -> u32 {
+    Day3.part2(&Day3.parse(input).unwrap()).unwrap()
 }
 
 #[test]
 fn adjacent1() {
-    let part = EnginePart::new("1234", 8, 2);
+    let part = EnginePart::new("1234", 8, 2).unwrap();
     let mut symbols = HashMap::new();
     symbols.insert(Point::new(3, 2), 'c');
     assert!(!symbol_adjacent(&part, &symbols));
@@ -175,18 +203,10 @@ fn example() {
     assert_eq!(part2(input), 467835);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(3).unwrap();
-    assert_eq!(part1(input), 525119);
-    assert_eq!(part2(input), 76504829);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(3).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(3, 1, part1(input));
+    assert_answer(3, 2, part2(input));
 }
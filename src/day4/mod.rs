@@ -1,83 +1,70 @@
-extern crate test;
+//! [Day 4: Scratchcards](https://adventofcode.com/2023/day/4)
 
-use itertools::Itertools;
 use std::collections::HashSet;
 
-#[cfg(test)]
-use test::Bencher;
+use crate::parse::{numbers, parse_lines, split_once, ParseError, ParseResult};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
-use crate::utils::read_input_to_string;
+fn winners(input: &str) -> ParseResult<Vec<u32>> {
+    let (winning, ours) = split_once(input, "|", "[winning] | [numbers]")?;
+    let win_set: HashSet<u32> = numbers(winning)?.into_iter().collect();
+    let our_numbers: Vec<u32> = numbers(ours)?;
+    Ok(our_numbers
+        .into_iter()
+        .filter(|v| win_set.contains(v))
+        .collect())
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    /// The number of winning numbers on each card
+    type Input<'a> = Vec<usize>;
+    type Answer1 = u32;
+    type Answer2 = u32;
 
-fn winners(input: &str) -> Vec<u32> {
-    if let Some((winning, numbers)) = input.split('|').collect_tuple() {
-        let win_set: HashSet<u32> = winning
-            .split_whitespace()
-            .map(str::parse)
-            .collect::<Result<HashSet<u32>, _>>()
-            .unwrap();
-        let our_numbers: Vec<u32> = numbers
-            .split_whitespace()
-            .map(str::parse)
-            .collect::<Result<Vec<u32>, _>>()
-            .unwrap();
-        our_numbers
-            .into_iter()
-            .filter(|v| win_set.contains(v))
-            .collect()
-    } else {
-        panic!("Did not find the pattern [winning]|[numbers], got {input}")
+    fn parse(&self, input: &str) -> Result<Vec<usize>, ParseError> {
+        parse_lines(input, |line| {
+            let (_, numbers) = split_once(line, ":", "Card [n]: [numbers]")?;
+            Ok(winners(numbers)?.len())
+        })
     }
-}
 
-fn part1(input: &str) -> u32 {
-    let mut score = 0;
-    for line in input.lines() {
-        let winning_numbers = if let Some((_, numbers)) = line.split(':').collect_tuple() {
-            winners(numbers)
-        } else {
-            panic!("Did not find pattern Card [n]:[numbers]")
-        };
-        if !winning_numbers.is_empty() {
-            score += 2_u32.pow(winning_numbers.len().saturating_sub(1) as u32);
-        }
+    fn part1(&self, win_counts: &Vec<usize>) -> u32 {
+        win_counts
+            .iter()
+            .filter(|count| **count > 0)
+            .map(|count| 2_u32.pow(*count as u32 - 1))
+            .sum()
     }
-    score
-}
 
-fn part2(input: &str) -> u32 {
-    let win_counts: Vec<usize> = input
-        .lines()
-        .map(|line| {
-            if let Some((_, numbers)) = line.split(':').collect_tuple() {
-                winners(numbers).len()
-            } else {
-                panic!("Did not find pattern Card [n]:[numbers]")
+    fn part2(&self, win_counts: &Vec<usize>) -> Option<u32> {
+        let mut card_counts = vec![1; win_counts.len()];
+        for (i, count) in win_counts.iter().enumerate() {
+            let n = card_counts[i];
+            for v in &mut card_counts[i + 1..i + 1 + *count] {
+                *v += n;
             }
-        })
-        .collect();
-    let mut card_counts = vec![1; win_counts.len()];
-    for (i, count) in win_counts.iter().enumerate() {
-        let n = card_counts[i];
-        for v in &mut card_counts[i + 1..i + 1 + *count] {
-            *v += n;
         }
+        Some(card_counts.iter().sum())
     }
-    card_counts.iter().sum()
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(4)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub fn part1(input: &str) -> u32 {
+    Day4.part1(&Day4.parse(input).unwrap())
+}
 
-    Ok(())
+pub fn part2(input: &str) -> u32 {
+    Day4.part2(&Day4.parse(input).unwrap()).unwrap()
 }
 
 #[test]
 fn card1_winners() {
     let input = "41 48 83 86 17 | 83 86  6 31 17  9 48 53";
 
-    assert_eq!(winners(input), [83, 86, 17, 48]);
+    assert_eq!(winners(input).unwrap(), [83, 86, 17, 48]);
 }
 
 #[test]
@@ -92,18 +79,10 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
     assert_eq!(part2(input), 30);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(4).unwrap();
-    assert_eq!(part1(input), 18519);
-    assert_eq!(part2(input), 11787590);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(4).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(4, 1, part1(input));
+    assert_answer(4, 2, part2(input));
 }
@@ -1,12 +1,13 @@
-extern crate test;
+//! [Day 5: If You Give A Seed A Fertilizer](https://adventofcode.com/2023/day/5)
 
 use itertools::Itertools;
 use std::ops::Range;
 
-#[cfg(test)]
-use test::Bencher;
-
-use crate::utils::read_input_to_string;
+use crate::generate::Rng;
+use crate::parse::{numbers, parse_blocks, split_block, split_once, ParseError, ParseResult};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 #[derive(Debug, Eq, PartialEq)]
 struct NumberMap {
@@ -23,49 +24,53 @@ impl NumberMap {
     }
 }
 
-fn parse_map(input: &str) -> NumberMap {
-    if let Some((destination_start, source_start, range_length)) =
-        input.split_whitespace().collect_tuple()
-    {
-        NumberMap::from_almanac(
-            destination_start.parse().unwrap(),
-            source_start.parse().unwrap(),
-            range_length.parse().unwrap(),
-        )
-    } else {
-        panic!("Expected the format `[destination] [source] [range_len]`, got {input}")
+fn parse_map(input: &str) -> ParseResult<NumberMap> {
+    match numbers(input)?[..] {
+        [destination_start, source_start, range_length] => Ok(NumberMap::from_almanac(
+            destination_start,
+            source_start,
+            range_length as u64,
+        )),
+        _ => Err(ParseError::format(
+            "[destination] [source] [range_len]",
+            input,
+        )),
     }
 }
 
-fn parse_seeds(input: &str) -> Vec<i64> {
-    if let Some((_, seeds)) = input.split(": ").collect_tuple() {
-        seeds
-            .split_whitespace()
-            .map(str::parse)
-            .collect::<Result<_, _>>()
-            .unwrap()
-    } else {
-        panic!("Invalid seeds format, got {input}")
-    }
+/// A block of a map's name followed by its ranges
+fn parse_map_block(block: &str) -> ParseResult<Vec<NumberMap>> {
+    block
+        .lines()
+        .enumerate()
+        .skip(1)
+        .map(|(i, line)| parse_map(line).map_err(|err| err.at_line(i + 1)))
+        .collect()
 }
 
-fn parse_seed_ranges(input: &str) -> Vec<Range<i64>> {
-    if let Some((_, seeds)) = input.split(": ").collect_tuple() {
-        let numbers: Vec<i64> = seeds
-            .split_whitespace()
-            .map(str::parse)
-            .collect::<Result<_, _>>()
-            .unwrap();
-        numbers
-            .into_iter()
-            .tuples()
-            .map(|(start, length)| start..start + length)
-            .collect()
-    } else {
-        panic!("Invalid seeds format, got {input}")
+fn parse_seeds(input: &str) -> ParseResult<Vec<i64>> {
+    let (_, seeds) = split_once(input, ": ", "seeds: [numbers]")?;
+    let seeds = numbers(seeds)?;
+    match seeds.is_empty() {
+        true => Err(ParseError::missing("seeds")),
+        false => Ok(seeds),
     }
 }
 
+fn seed_ranges(seeds: &[i64]) -> Vec<Range<i64>> {
+    seeds
+        .iter()
+        .tuples()
+        .map(|(start, length)| *start..start + length)
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct Almanac {
+    seeds: Vec<i64>,
+    maps: Vec<Vec<NumberMap>>,
+}
+
 fn map_to_next(num: i64, mapping: &[NumberMap]) -> i64 {
     let offset = mapping
         .iter()
@@ -80,21 +85,14 @@ fn map_to_next(num: i64, mapping: &[NumberMap]) -> i64 {
     num + offset
 }
 
-fn part1(input: &str) -> i64 {
-    let mut blocks = input.split("\n\n");
-    let seeds = parse_seeds(blocks.next().unwrap());
-
-    let mut maps: Vec<Vec<NumberMap>> = vec![];
-
-    for block in blocks {
-        let mut lines = block.lines();
-        lines.next().unwrap();
-        maps.push(lines.map(parse_map).collect_vec())
-    }
+pub fn part1(input: &str) -> i64 {
+    Day5.part1(&Day5.parse(input).unwrap())
+}
 
-    let mut seed_changes = seeds;
+fn lowest_location(Almanac { seeds, maps }: &Almanac) -> i64 {
+    let mut seed_changes = seeds.clone();
 
-    for map in &maps {
+    for map in maps {
         for v in &mut seed_changes {
             *v = map_to_next(*v, map)
         }
@@ -138,22 +136,16 @@ fn add_to_range(range: Range<i64>, value: i64) -> Range<i64> {
     (range.start + value)..(range.end + value)
 }
 
-fn part2(input: &str) -> i64 {
-    let mut blocks = input.split("\n\n");
-    let mut seeds = parse_seed_ranges(blocks.next().unwrap());
-
-    let mut maps: Vec<Vec<NumberMap>> = vec![];
-
-    for block in blocks {
-        let mut lines = block.lines();
-        lines.next().unwrap();
-        maps.push(lines.map(parse_map).collect_vec())
-    }
+pub fn part2(input: &str) -> i64 {
+    Day5.part2(&Day5.parse(input).unwrap()).unwrap()
+}
 
+fn lowest_range_location(Almanac { seeds, maps }: &Almanac) -> i64 {
+    let mut seeds = seed_ranges(seeds);
     let mut seeds_transferred: Vec<Range<i64>> = vec![];
     let mut seeds_processing: Vec<Range<i64>> = vec![];
 
-    for map in &maps {
+    for map in maps {
         seeds.append(&mut seeds_transferred);
         for map_range in map {
             seeds.append(&mut seeds_processing);
@@ -174,18 +166,96 @@ fn part2(input: &str) -> i64 {
     seeds_transferred.iter().map(|v| v.start).min().unwrap()
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(5)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input<'a> = Almanac;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(&self, input: &str) -> Result<Almanac, ParseError> {
+        let (seeds, maps) = split_block(input).ok_or_else(|| ParseError::missing("maps"))?;
+        Ok(Almanac {
+            seeds: parse_seeds(seeds).map_err(|err| err.at_line(1))?,
+            maps: parse_blocks(maps, parse_map_block)
+                .map_err(|err| err.offset(seeds.lines().count() + 1))?,
+        })
+    }
+
+    fn part1(&self, almanac: &Almanac) -> i64 {
+        lowest_location(almanac)
+    }
+
+    fn part2(&self, almanac: &Almanac) -> Option<i64> {
+        Some(lowest_range_location(almanac))
+    }
+}
+
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+/// An almanac with `size` seed ranges and maps of up to `size` ranges. A map moves consecutive
+/// blocks of numbers around, some of them are left out and keep their numbers
+pub fn generate(size: usize, rng: &mut Rng) -> String {
+    generate_within(size, 4_000_000_000, rng)
+}
+
+/// `generate` with numbers of about up to `span`, which must be at least `size`
+fn generate_within(size: usize, span: i64, rng: &mut Rng) -> String {
+    let average = span / size as i64;
+    let seeds = (0..size)
+        .flat_map(|_| [rng.range(0..span), rng.range(1..average + 1)])
+        .join(" ");
+
+    let maps = CATEGORIES
+        .iter()
+        .tuple_windows()
+        .map(|(source, destination)| {
+            let lengths = (0..size).map(|_| rng.range(1..2 * average)).collect_vec();
+            let source_starts = lengths
+                .iter()
+                .scan(0, |start, length| {
+                    *start += length;
+                    Some(*start - length)
+                })
+                .collect_vec();
+            let mut order = (0..size).collect_vec();
+            rng.shuffle(&mut order);
+            let mut destination_starts = vec![0; size];
+            let mut next_start = 0;
+            for i in order {
+                destination_starts[i] = next_start;
+                next_start += lengths[i];
+            }
 
-    Ok(())
+            let mut ranges = (0..size)
+                .filter(|i| *i == 0 || rng.below(8) != 0)
+                .map(|i| {
+                    format!(
+                        "{} {} {}",
+                        destination_starts[i], source_starts[i], lengths[i]
+                    )
+                })
+                .collect_vec();
+            rng.shuffle(&mut ranges);
+            format!("{source}-to-{destination} map:\n{}", ranges.join("\n"))
+        });
+
+    format!("seeds: {seeds}\n\n{}", maps.collect_vec().join("\n\n"))
 }
 
 #[test]
 fn test_parse_seed_ranges() {
-    let input = "seeds: 79 14 55 13";
-    assert_eq!(parse_seed_ranges(input), vec![79..93, 55..68]);
+    let seeds = parse_seeds("seeds: 79 14 55 13").unwrap();
+    assert_eq!(seed_ranges(&seeds), vec![79..93, 55..68]);
 }
 
 #[test]
@@ -193,7 +263,11 @@ fn seed_to_soil() {
     let input = "50 98 2
 52 50 48";
 
-    let number_maps = input.lines().map(parse_map).collect_vec();
+    let number_maps = input
+        .lines()
+        .map(parse_map)
+        .collect::<ParseResult<Vec<_>>>()
+        .unwrap();
     assert_eq!(
         number_maps,
         vec![
@@ -216,6 +290,7 @@ fn test_range_intersection() {
 }
 
 #[test]
+#[allow(clippy::single_range_in_vec_init)]
 fn test_separate_intersection() {
     assert_eq!(
         separate_intersection(1..9, &(7..12)),
@@ -223,6 +298,32 @@ fn test_separate_intersection() {
     );
 }
 
+#[test]
+fn generated() {
+    let almanac = Day5.parse(&generate(20, &mut Rng::new(5))).unwrap();
+    assert_eq!(almanac.seeds.len(), 40);
+    assert_eq!(almanac.maps.len(), 7);
+    assert!(almanac.maps.iter().all(|map| map.len() <= 20));
+
+    // Small enough to map every seed
+    for rng_seed in 0..20 {
+        let small = Day5
+            .parse(&generate_within(4, 200, &mut Rng::new(rng_seed)))
+            .unwrap();
+        let brute_force = seed_ranges(&small.seeds)
+            .into_iter()
+            .flatten()
+            .map(|seed| {
+                small
+                    .maps
+                    .iter()
+                    .fold(seed, |number, map| map_to_next(number, map))
+            })
+            .min();
+        assert_eq!(Day5.part2(&small), brute_force);
+    }
+}
+
 #[test]
 fn example() {
     let input = "seeds: 79 14 55 13
@@ -260,20 +361,18 @@ humidity-to-location map:
 56 93 4";
     assert_eq!(part1(input), 35);
     assert_eq!(part2(input), 46);
+
+    let err = Day5.parse(&input.replace("0 11 42", "0 11")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 14: expected [destination] [source] [range_len], found '0 11'"
+    );
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(5).unwrap();
-    assert_eq!(part1(input), 382895070);
-    assert_eq!(part2(input), 17729182);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(5).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(5, 1, part1(input));
+    assert_answer(5, 2, part2(input));
 }
@@ -1,11 +1,11 @@
-extern crate test;
+//! [Day 6: Wait For It](https://adventofcode.com/2023/day/6)
 
 use itertools::Itertools;
 
-#[cfg(test)]
-use test::Bencher;
-
-use crate::utils::read_input_to_string;
+use crate::parse::{number, numbers, split_once, ParseError, ParseResult};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 fn distance(total_time: i64, hold_time: i64) -> i64 {
     if hold_time >= total_time {
@@ -27,20 +27,30 @@ impl Race {
     }
 }
 
-fn parse_races(input: &str) -> Vec<Race> {
-    if let Some((times, distances)) = input.lines().collect_tuple() {
-        times
-            .split_whitespace()
-            .skip(1)
-            .zip(distances.split_whitespace().skip(1))
-            .map(|(time, record)| match (time.parse(), record.parse()) {
-                (Ok(time), Ok(record)) => Race { time, record },
-                _ => panic!("Could not parse time {time} record {record}"),
-            })
-            .collect()
-    } else {
-        panic!("Unexpected race format {input}")
+/// The times and distances after their labels, which are on the first and second line
+fn race_lines(input: &str) -> ParseResult<(&str, &str)> {
+    let (times, distances) = input
+        .lines()
+        .collect_tuple()
+        .ok_or_else(|| ParseError::format("a Time and a Distance line", input.trim()))?;
+    let (_, times) = split_once(times, ":", "Time: [times]").map_err(|err| err.at_line(1))?;
+    let (_, distances) =
+        split_once(distances, ":", "Distance: [distances]").map_err(|err| err.at_line(2))?;
+    Ok((times, distances))
+}
+
+fn parse_races(input: &str) -> ParseResult<Vec<Race>> {
+    let (times, distances) = race_lines(input)?;
+    let times: Vec<i64> = numbers(times).map_err(|err| err.at_line(1))?;
+    let records: Vec<i64> = numbers(distances).map_err(|err| err.at_line(2))?;
+    if times.len() != records.len() {
+        return Err(ParseError::format("a distance for every time", distances).at_line(2));
     }
+    Ok(times
+        .into_iter()
+        .zip(records)
+        .map(|(time, record)| Race { time, record })
+        .collect())
 }
 
 fn binary_search_left<F>(func: F, target: i64, low: i64, peak: i64) -> i64
@@ -101,26 +111,22 @@ where
     (left, right)
 }
 
-fn parse_races2(input: &str) -> Vec<Race> {
-    if let Some((times, distances)) = input.lines().collect_tuple() {
-        vec![Race::new(
-            times
-                .replace("Time: ", "")
-                .replace(' ', "")
-                .parse()
-                .unwrap(),
-            distances
-                .replace("Distance: ", "")
-                .replace(' ', "")
-                .parse()
-                .unwrap(),
-        )]
-    } else {
-        panic!("Unexpected race format {input}")
-    }
+/// The races of part 2, where the spaces between the digits are bad kerning
+fn parse_races2(input: &str) -> ParseResult<Vec<Race>> {
+    let (times, distances) = race_lines(input)?;
+    Ok(vec![Race::new(
+        number(&times.replace(' ', "")).map_err(|err| err.at_line(1))?,
+        number(&distances.replace(' ', "")).map_err(|err| err.at_line(2))?,
+    )])
 }
 
-fn parts(races: Vec<Race>) -> usize {
+/// The races read both ways
+pub struct Races {
+    separate: Vec<Race>,
+    kerned: Vec<Race>,
+}
+
+fn parts(races: &[Race]) -> usize {
     let mut acc = 1;
     for race in races {
         let range = find_threshold_range(
@@ -136,24 +142,35 @@ fn parts(races: Vec<Race>) -> usize {
     acc
 }
 
-fn part1(input: &str) -> usize {
-    let races = parse_races(input);
+pub fn part1(input: &str) -> usize {
+    Day6.part1(&Day6.parse(input).unwrap())
+}
 
-    parts(races)
+pub fn part2(input: &str) -> usize {
+    Day6.part2(&Day6.parse(input).unwrap()).unwrap()
 }
 
-fn part2(input: &str) -> usize {
-    let races = parse_races2(input);
+pub struct Day6;
 
-    parts(races)
-}
+impl Solution for Day6 {
+    type Input<'a> = Races;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(6)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+    fn parse(&self, input: &str) -> Result<Races, ParseError> {
+        Ok(Races {
+            separate: parse_races(input)?,
+            kerned: parse_races2(input)?,
+        })
+    }
 
-    Ok(())
+    fn part1(&self, races: &Races) -> usize {
+        parts(&races.separate)
+    }
+
+    fn part2(&self, races: &Races) -> Option<usize> {
+        Some(parts(&races.kerned))
+    }
 }
 
 #[test]
@@ -162,7 +179,7 @@ fn example_parse() {
 Distance:  9  40  200";
     assert_eq!(
         parse_races(input),
-        vec![Race::new(7, 9), Race::new(15, 40), Race::new(30, 200)]
+        Ok(vec![Race::new(7, 9), Race::new(15, 40), Race::new(30, 200)])
     );
 }
 
@@ -170,10 +187,7 @@ Distance:  9  40  200";
 fn example_parse2() {
     let input = "Time:      7  15   30
 Distance:  9  40  200";
-    assert_eq!(
-        parse_races(input),
-        vec![Race::new(7, 9), Race::new(15, 40), Race::new(30, 200)]
-    );
+    assert_eq!(parse_races2(input), Ok(vec![Race::new(71530, 940200)]));
 }
 
 #[test]
@@ -184,18 +198,10 @@ Distance:  9  40  200";
     assert_eq!(part2(input), 71503);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(6).unwrap();
-    assert_eq!(part1(input), 293046);
-    assert_eq!(part2(input), 35150181);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(6).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(6, 1, part1(input));
+    assert_answer(6, 2, part2(input));
 }
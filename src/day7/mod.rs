@@ -1,14 +1,14 @@
-extern crate test;
+//! [Day 7: Camel Cards](https://adventofcode.com/2023/day/7)
 
 use itertools::Itertools;
 use std::collections::HashMap;
 use std::hash::Hash;
 
 use crate::day7::HandType::{FiveKind, FourKind, FullHouse, HighCard, OnePair, ThreeKind, TwoPair};
-#[cfg(test)]
-use test::Bencher;
-
-use crate::utils::read_input_to_string;
+use crate::parse::{number, parse_lines, split_once, ParseError, ParseResult};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Hash, Copy, Clone)]
 struct Card {
@@ -151,16 +151,22 @@ impl Hand {
     }
 }
 
-fn parse_hands(input: &str, card_map: &CardMap) -> Vec<(Hand, u32)> {
-    input
-        .lines()
-        .map(|line| {
-            if let Some((hand, bid)) = line.split_whitespace().collect_tuple() {
-                (Hand::from_str_map(hand, card_map), bid.parse().unwrap())
-            } else {
-                panic!("bad hand")
-            }
-        })
+/// A hand of five cards and its bid, the cards are read once the rules are known
+fn parse_hand(line: &str) -> ParseResult<(&str, u32)> {
+    let (hand, bid) = split_once(line, " ", "[hand] [bid]")?;
+    if hand.chars().count() != 5 {
+        return Err(ParseError::format("five cards", hand));
+    }
+    if let Some(card) = hand.chars().find(|c| !"23456789TJQKA".contains(*c)) {
+        return Err(ParseError::unknown("card", card));
+    }
+    Ok((hand, number(bid)?))
+}
+
+fn deal_hands(hands: &[(&str, u32)], card_map: &CardMap) -> Vec<(Hand, u32)> {
+    hands
+        .iter()
+        .map(|(hand, bid)| (Hand::from_str_map(hand, card_map), *bid))
         .collect()
 }
 
@@ -174,15 +180,22 @@ fn winnings(hands: &mut [(Hand, u32)]) -> usize {
         .sum()
 }
 
-fn part1(input: &str) -> usize {
+pub fn part1(input: &str) -> usize {
+    Day7.part1(&Day7.parse(input).unwrap())
+}
+
+pub fn part2(input: &str) -> usize {
+    Day7.part2(&Day7.parse(input).unwrap()).unwrap()
+}
+
+fn total_winnings(hands: &[(&str, u32)]) -> usize {
     let card_map = CardMap::from_string("23456789TJQKA");
-    let mut hands = parse_hands(input, &card_map);
-    winnings(&mut hands)
+    winnings(&mut deal_hands(hands, &card_map))
 }
 
-fn part2(input: &str) -> usize {
+fn joker_winnings(hands: &[(&str, u32)]) -> usize {
     let card_map = CardMap::from_string("J23456789TQKA");
-    let mut hands = parse_hands(input, &card_map);
+    let mut hands = deal_hands(hands, &card_map);
     hands
         .iter_mut()
         .for_each(|(hand, _)| hand.typ = HandType::joker_upgrade(&hand.typ, hand.joker_count()));
@@ -190,12 +203,25 @@ fn part2(input: &str) -> usize {
     winnings(&mut hands)
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(7)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub struct Day7;
+
+impl Solution for Day7 {
+    /// The hands with their bids
+    type Input<'a> = Vec<(&'a str, u32)>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Vec<(&'a str, u32)>, ParseError> {
+        parse_lines(input, parse_hand)
+    }
+
+    fn part1(&self, hands: &Vec<(&str, u32)>) -> usize {
+        total_winnings(hands)
+    }
 
-    Ok(())
+    fn part2(&self, hands: &Vec<(&str, u32)>) -> Option<usize> {
+        Some(joker_winnings(hands))
+    }
 }
 
 #[test]
@@ -238,18 +264,10 @@ QQQJA 483";
     assert_eq!(part2(input), 5905);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(7).unwrap();
-    assert_eq!(part1(input), 253313241);
-    assert_eq!(part2(input), 253362743);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(7).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(7, 1, part1(input));
+    assert_answer(7, 2, part2(input));
 }
@@ -1,4 +1,4 @@
-extern crate test;
+//! [Day 8: Haunted Wasteland](https://adventofcode.com/2023/day/8)
 
 use num_integer::gcd;
 use std::collections::HashMap;
@@ -6,10 +6,11 @@ use std::iter::Cycle;
 use std::str::FromStr;
 
 use itertools::Itertools;
-#[cfg(test)]
-use test::Bencher;
 
-use crate::utils::read_input_to_string;
+use crate::parse::{parse_lines, split_block, split_once, ParseError, ParseResult};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 #[derive(Debug)]
 struct Graph {
@@ -28,50 +29,35 @@ impl Graph {
     }
 }
 
-fn extract_codes(input: &str) -> (String, String, String) {
-    (
-        input[0..3].into(),
-        input[7..10].into(),
-        input[12..15].into(),
-    )
+fn extract_codes(input: &str) -> ParseResult<(String, String, String)> {
+    let (node, children) = split_once(input, " = ", "[node] = ([left], [right])")?;
+    let (left, right) = children
+        .strip_prefix('(')
+        .and_then(|children| children.strip_suffix(')'))
+        .and_then(|children| children.split_once(", "))
+        .ok_or_else(|| ParseError::format("([left], [right])", children))?;
+    Ok((node.into(), left.into(), right.into()))
 }
 
 impl FromStr for Graph {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut graph = Graph::new();
 
-        s.lines()
-            .map(extract_codes)
-            .for_each(|(node, left, right)| graph.add_edge(node, left, right));
+        for (node, left, right) in parse_lines(s, extract_codes)? {
+            graph.add_edge(node, left, right);
+        }
 
         Ok(graph)
     }
 }
 
-fn skip_first_two_lines(input: &str) -> &str {
-    let mut lines_skipped = 0;
-    let mut start_index = 0;
-
-    for (index, character) in input.char_indices() {
-        if character == '\n' {
-            lines_skipped += 1;
-            if lines_skipped == 2 {
-                start_index = index + 1;
-                break;
-            }
-        }
-    }
-
-    &input[start_index..]
-}
-
 struct ExitStepsIter<'a> {
     current_node: &'a str,
     graph: &'a Graph,
     directions: Cycle<std::str::Chars<'a>>,
-    steps: usize,
+    steps: u64,
 }
 
 impl<'a> ExitStepsIter<'a> {
@@ -86,7 +72,7 @@ impl<'a> ExitStepsIter<'a> {
 }
 
 impl<'a> Iterator for ExitStepsIter<'a> {
-    type Item = usize;
+    type Item = u64;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
@@ -112,53 +98,79 @@ impl<'a> Iterator for ExitStepsIter<'a> {
     }
 }
 
-fn part1(input: &str) -> usize {
-    let directions = input.lines().next().unwrap();
-    let graph: Graph = skip_first_two_lines(input.trim()).parse().unwrap();
-
-    let mut steps_iter = ExitStepsIter::new("AAA", &graph, directions);
-    steps_iter.next().unwrap()
+fn lcm(a: u64, b: u64) -> u64 {
+    (a * b) / gcd(a, b)
 }
 
-fn lcm(a: usize, b: usize) -> usize {
-    (a * b) / gcd(a, b)
+#[derive(Debug)]
+pub struct Map<'a> {
+    directions: &'a str,
+    graph: Graph,
 }
 
-fn part2(input: &str) -> usize {
-    let directions = input.lines().next().unwrap();
-    let graph: Graph = skip_first_two_lines(input.trim()).parse().unwrap();
+pub struct Day8;
 
-    let start_nodes: Vec<&str> = graph
-        .edges
-        .keys()
-        .filter_map(|v| {
-            if v.ends_with('A') {
-                Some(AsRef::as_ref(v))
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    let exit_deltas = start_nodes
-        .iter()
-        .map(|node| {
-            let mut step_iter = ExitStepsIter::new(node, &graph, directions);
-            let a = step_iter.next().unwrap();
-            let b = step_iter.next().unwrap();
-            b - a
+impl Solution for Day8 {
+    type Input<'a> = Map<'a>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Map<'a>, ParseError> {
+        let (directions, graph) =
+            split_block(input).ok_or_else(|| ParseError::missing("network"))?;
+        if let Some(direction) = directions.chars().find(|c| !matches!(c, 'L' | 'R')) {
+            return Err(ParseError::unknown("direction", direction).at_line(1));
+        }
+        if directions.is_empty() {
+            return Err(ParseError::missing("directions").at_line(1));
+        }
+        Ok(Map {
+            directions,
+            graph: graph
+                .trim_end()
+                .parse()
+                .map_err(|err: ParseError| err.offset(2))?,
         })
-        .collect_vec();
+    }
+
+    fn part1(&self, Map { directions, graph }: &Map) -> u64 {
+        let mut steps_iter = ExitStepsIter::new("AAA", graph, directions);
+        steps_iter.next().unwrap()
+    }
 
-    exit_deltas.iter().fold(1, |acc, num| lcm(acc, *num))
+    fn part2(&self, Map { directions, graph }: &Map) -> Option<u64> {
+        let start_nodes: Vec<&str> = graph
+            .edges
+            .keys()
+            .filter_map(|v| {
+                if v.ends_with('A') {
+                    Some(AsRef::as_ref(v))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        let exit_deltas = start_nodes
+            .iter()
+            .map(|node| {
+                let mut step_iter = ExitStepsIter::new(node, graph, directions);
+                let a = step_iter.next().unwrap();
+                let b = step_iter.next().unwrap();
+                b - a
+            })
+            .collect_vec();
+
+        Some(exit_deltas.iter().fold(1, |acc, num| lcm(acc, *num)))
+    }
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(8)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub fn part1(input: &str) -> u64 {
+    Day8.part1(&Day8.parse(input).unwrap())
+}
 
-    Ok(())
+pub fn part2(input: &str) -> u64 {
+    Day8.part2(&Day8.parse(input).unwrap()).unwrap()
 }
 
 #[test]
@@ -183,6 +195,14 @@ AAA = (BBB, BBB)
 BBB = (AAA, ZZZ)
 ZZZ = (ZZZ, ZZZ)";
     assert_eq!(part1(input), 6);
+
+    let err = Day8
+        .parse(&input.replace("(AAA, ZZZ)", "(AAA ZZZ)"))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 4: expected ([left], [right]), found '(AAA ZZZ)'"
+    );
 }
 
 #[test]
@@ -200,18 +220,10 @@ XXX = (XXX, XXX)";
     assert_eq!(part2(input), 6);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(8).unwrap();
-    assert_eq!(part1(input), 22357);
-    assert_eq!(part2(input), 10371555451871);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(8).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(8, 1, part1(input));
+    assert_answer(8, 2, part2(input));
 }
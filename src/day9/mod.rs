@@ -1,11 +1,11 @@
-extern crate test;
+//! [Day 9: Mirage Maintenance](https://adventofcode.com/2023/day/9)
 
 use itertools::Itertools;
 
-#[cfg(test)]
-use test::Bencher;
-
-use crate::utils::read_input_to_string;
+use crate::parse::{numbers, parse_lines, ParseError};
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
 fn diff<I>(iter: I) -> impl Iterator<Item = i64>
 where
@@ -26,41 +26,38 @@ fn predict_next(seq: Vec<i64>) -> i64 {
         .fold(0, |acc, next| acc + next.last().unwrap())
 }
 
-fn part1(input: &str) -> i64 {
-    input
-        .lines()
-        .map(|line| {
-            let seq = line
-                .split_whitespace()
-                .map(str::parse)
-                .collect::<Result<_, _>>()
-                .unwrap();
-            predict_next(seq)
-        })
-        .sum()
-}
+pub struct Day9;
 
-fn part2(input: &str) -> i64 {
-    input
-        .lines()
-        .map(|line| {
-            let seq = line
-                .split_whitespace()
-                .rev()
-                .map(str::parse)
-                .collect::<Result<_, _>>()
-                .unwrap();
-            predict_next(seq)
+impl Solution for Day9 {
+    type Input<'a> = Vec<Vec<i64>>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(&self, input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
+        parse_lines(input, |line| match numbers(line)? {
+            values if values.is_empty() => Err(ParseError::missing("values")),
+            values => Ok(values),
         })
-        .sum()
+    }
+
+    fn part1(&self, sequences: &Vec<Vec<i64>>) -> i64 {
+        sequences.iter().cloned().map(predict_next).sum()
+    }
+
+    fn part2(&self, sequences: &Vec<Vec<i64>>) -> Option<i64> {
+        let reversed = sequences
+            .iter()
+            .map(|seq| seq.iter().rev().copied().collect());
+        Some(reversed.map(predict_next).sum())
+    }
 }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(9)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+pub fn part1(input: &str) -> i64 {
+    Day9.part1(&Day9.parse(input).unwrap())
+}
 
-    Ok(())
+pub fn part2(input: &str) -> i64 {
+    Day9.part2(&Day9.parse(input).unwrap()).unwrap()
 }
 
 #[test]
@@ -72,18 +69,10 @@ fn example() {
     assert_eq!(part2(input), 2);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
     let input = &read_input_to_string(9).unwrap();
-    assert_eq!(part1(input), 1974913025);
-    assert_eq!(part2(input), 884);
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(9).unwrap();
-        part1(input);
-        part2(input);
-    })
+    assert_answer(9, 1, part1(input));
+    assert_answer(9, 2, part2(input));
 }
@@ -0,0 +1,379 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+#[cfg(test)]
+use std::fs;
+use std::io;
+#[cfg(test)]
+use std::io::ErrorKind;
+use std::path::Path;
+#[cfg(test)]
+use std::path::PathBuf;
+
+#[cfg(feature = "curl")]
+use curl::easy::Easy;
+
+use crate::scaffold::{new_day, TEMPLATE_FILE};
+use crate::store::{InputKey, InputStore};
+
+pub const TOKEN_ENV: &str = "TOKEN";
+pub const OFFLINE_ENV: &str = "AOC_OFFLINE";
+
+#[cfg(feature = "curl")]
+const AOC_URL: &str = "https://adventofcode.com";
+
+#[derive(Debug)]
+pub enum FetchError {
+    MissingToken,
+    Status {
+        url: String,
+        code: u32,
+    },
+    #[cfg(feature = "curl")]
+    Curl(curl::Error),
+    Io(io::Error),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::MissingToken => {
+                write!(f, "session token not found, set the {TOKEN_ENV} env var")
+            }
+            FetchError::Status { url, code } => write!(f, "got response code {code} from {url}"),
+            #[cfg(feature = "curl")]
+            FetchError::Curl(err) => write!(f, "request failed: {err}"),
+            FetchError::Io(err) => write!(f, "io error: {err}"),
+        }
+    }
+}
+
+impl Error for FetchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "curl")]
+            FetchError::Curl(err) => Some(err),
+            FetchError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "curl")]
+impl From<curl::Error> for FetchError {
+    fn from(err: curl::Error) -> Self {
+        FetchError::Curl(err)
+    }
+}
+
+impl From<io::Error> for FetchError {
+    fn from(err: io::Error) -> Self {
+        FetchError::Io(err)
+    }
+}
+
+/// Something that can talk to the AoC site, e.g. GET `/2023/day/1/input`
+pub trait Fetch {
+    fn get(&mut self, path: &str) -> Result<Vec<u8>, FetchError>;
+
+    /// POST an urlencoded form, e.g. `level=1&answer=42` to `/2023/day/1/answer`
+    fn post(&mut self, path: &str, form: &str) -> Result<Vec<u8>, FetchError>;
+}
+
+/// The client for the real site, behind the `curl` feature
+#[cfg(feature = "curl")]
+pub struct CurlFetch {
+    token: Option<String>,
+    base_url: String,
+}
+
+#[cfg(feature = "curl")]
+impl CurlFetch {
+    pub fn new(token: Option<String>, base_url: impl Into<String>) -> Self {
+        Self {
+            token,
+            base_url: base_url.into(),
+        }
+    }
+
+    /// The token is only required once something is actually fetched
+    pub fn from_env() -> Self {
+        Self::new(env::var(TOKEN_ENV).ok(), AOC_URL)
+    }
+
+    fn perform(&self, path: &str, form: Option<&str>) -> Result<Vec<u8>, FetchError> {
+        let token = self.token.as_ref().ok_or(FetchError::MissingToken)?;
+        let url = format!("{}{path}", self.base_url);
+
+        let mut data = Vec::new();
+        let mut easy = Easy::new();
+        easy.useragent("https://github.com/areeh")?;
+        easy.cookie(&format!("session={token}"))?;
+        easy.url(&url)?;
+        match form {
+            Some(form) => easy.post_fields_copy(form.as_bytes())?,
+            None => easy.get(true)?,
+        }
+        {
+            let mut transfer = easy.transfer();
+            transfer.write_function(|chunk| {
+                data.extend_from_slice(chunk);
+                Ok(chunk.len())
+            })?;
+            transfer.perform()?;
+        }
+
+        match easy.response_code()? {
+            200 => Ok(data),
+            code => Err(FetchError::Status { url, code }),
+        }
+    }
+}
+
+#[cfg(feature = "curl")]
+impl Fetch for CurlFetch {
+    fn get(&mut self, path: &str) -> Result<Vec<u8>, FetchError> {
+        self.perform(path, None)
+    }
+
+    fn post(&mut self, path: &str, form: &str) -> Result<Vec<u8>, FetchError> {
+        self.perform(path, Some(form))
+    }
+}
+
+/// Serves files from a local directory laid out like the site
+#[cfg(test)]
+pub struct LocalFetch {
+    root: PathBuf,
+}
+
+#[cfg(test)]
+impl LocalFetch {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[cfg(test)]
+impl Fetch for LocalFetch {
+    fn get(&mut self, path: &str) -> Result<Vec<u8>, FetchError> {
+        let mut file = self.root.join(path.trim_start_matches('/'));
+        if file.is_dir() {
+            file.push("index.html");
+        }
+        fs::read(&file).map_err(|err| match err.kind() {
+            ErrorKind::NotFound => FetchError::Status {
+                url: file.display().to_string(),
+                code: 404,
+            },
+            _ => FetchError::Io(err),
+        })
+    }
+
+    fn post(&mut self, path: &str, _form: &str) -> Result<Vec<u8>, FetchError> {
+        Err(FetchError::Status {
+            url: self
+                .root
+                .join(path.trim_start_matches('/'))
+                .display()
+                .to_string(),
+            code: 405,
+        })
+    }
+}
+
+/// A single threaded HTTP server answering each request with the next of `responses`.
+/// Returns its base url and a handle yielding the raw requests it got
+#[cfg(all(test, feature = "curl"))]
+pub fn mock_server(
+    responses: Vec<(u32, String)>,
+) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for (code, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                request.push_str(&line);
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+            }
+            let mut form = vec![0; content_length];
+            reader.read_exact(&mut form).unwrap();
+            request.push_str(&String::from_utf8(form).unwrap());
+            requests.push(request);
+
+            write!(
+                stream,
+                "HTTP/1.1 {code} Mock\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+        requests
+    });
+    (base_url, handle)
+}
+
+pub fn offline_from_env() -> bool {
+    env::var(OFFLINE_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Downloads the input into the store unless it is already there
+pub fn fetch_input(
+    store: &InputStore,
+    year: i32,
+    day: u32,
+    client: &mut dyn Fetch,
+) -> Result<(), FetchError> {
+    let key = InputKey::new(year, day);
+    if !store.contains(&key) {
+        let input = client.get(&format!("/{year}/day/{day}/input"))?;
+        store.write(&key, input)?;
+    }
+    Ok(())
+}
+
+/// Fetches the input, and for a day without a module yet the puzzle description, into the store
+/// before scaffolding the module. Returns whether a module was created. Outside of the repository,
+/// without `src_dir`'s template, nothing but the input is fetched
+pub fn make_day(
+    src_dir: &Path,
+    store: &InputStore,
+    year: i32,
+    day: u32,
+    client: &mut dyn Fetch,
+) -> Result<bool, FetchError> {
+    fetch_input(store, year, day, client)?;
+
+    if src_dir.join(format!("day{day}")).join("mod.rs").exists() {
+        return Ok(false);
+    }
+    let template = src_dir.join(TEMPLATE_FILE);
+    if !template.exists() {
+        let message = format!("{} not found", template.display());
+        return Err(io::Error::new(io::ErrorKind::NotFound, message).into());
+    }
+    if !store.puzzle_path(year, day).exists() {
+        let puzzle = client.get(&format!("/{year}/day/{day}"))?;
+        store.write_puzzle(year, day, puzzle)?;
+    }
+
+    Ok(new_day(src_dir, store, year, day)?)
+}
+
+#[cfg(test)]
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("aoc-2023-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn make_day_from_local() {
+    let dir = scratch_dir("make-day");
+    let (src, site) = (dir.join("src"), dir.join("site"));
+    let store = InputStore::new(dir.join("inputs"));
+    fs::create_dir_all(site.join("2023/day/3")).unwrap();
+    fs::create_dir_all(&src).unwrap();
+    fs::write(site.join("2023/day/3/input"), "467..114..\n").unwrap();
+    fs::write(
+        site.join("2023/day/3/index.html"),
+        "<p>For example:</p><pre><code>467..\n...*.\n</code></pre>",
+    )
+    .unwrap();
+    fs::write(
+        src.join("template.rs"),
+        "// day {{day}}\nlet input = \"{{example}}\";\n",
+    )
+    .unwrap();
+    fs::write(src.join("lib.rs"), "pub mod day1;\npub mod utils;\n").unwrap();
+    fs::copy("src/registry.rs", src.join("registry.rs")).unwrap();
+
+    assert!(make_day(&src, &store, 2023, 3, &mut LocalFetch::new(&site)).unwrap());
+    assert_eq!(store.read(&InputKey::new(2023, 3)).unwrap(), "467..114..\n");
+    assert_eq!(
+        fs::read_to_string(src.join("day3/mod.rs")).unwrap(),
+        "// day 3\nlet input = \"467..\n...*.\";\n"
+    );
+    assert_eq!(
+        fs::read_to_string(src.join("lib.rs")).unwrap(),
+        "pub mod day1;\npub mod day3;\npub mod utils;\n"
+    );
+
+    // Existing inputs and modules are never refetched
+    fs::remove_dir_all(&site).unwrap();
+    assert!(!make_day(&src, &store, 2023, 3, &mut LocalFetch::new(&site)).unwrap());
+
+    assert!(matches!(
+        make_day(&src, &store, 2023, 4, &mut LocalFetch::new(&site)),
+        Err(FetchError::Status { code: 404, .. })
+    ));
+    assert!(!store.contains(&InputKey::new(2023, 4)));
+
+    // Without the template there is nothing to scaffold and no puzzle is fetched
+    fs::remove_file(src.join("template.rs")).unwrap();
+    store.write(&InputKey::new(2023, 5), "1\n").unwrap();
+    assert!(matches!(
+        make_day(&src, &store, 2023, 5, &mut LocalFetch::new(&site)),
+        Err(FetchError::Io(err)) if err.kind() == ErrorKind::NotFound
+    ));
+    assert!(!store.puzzle_path(2023, 5).exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn curl_against_mock_server() {
+    let (url, server) = mock_server(vec![
+        (200, "0 3 6 9\n".to_string()),
+        (200, "<article>ok</article>".to_string()),
+        (404, "nope".to_string()),
+    ]);
+    let mut client = CurlFetch::new(Some("abc".to_string()), url);
+
+    assert_eq!(client.get("/2023/day/9/input").unwrap(), b"0 3 6 9\n");
+    assert_eq!(
+        client
+            .post("/2023/day/9/answer", "level=1&answer=114")
+            .unwrap(),
+        b"<article>ok</article>"
+    );
+    assert!(matches!(
+        client.get("/2023/day/26/input"),
+        Err(FetchError::Status { code: 404, .. })
+    ));
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("GET /2023/day/9/input HTTP/1.1\r\n"));
+    assert!(requests[0].contains("Cookie: session=abc\r\n"));
+    assert!(requests[1].starts_with("POST /2023/day/9/answer HTTP/1.1\r\n"));
+    assert!(requests[1].ends_with("\r\n\r\nlevel=1&answer=114"));
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn missing_token() {
+    let mut client = CurlFetch::new(None, AOC_URL);
+    assert!(matches!(
+        client.get("/2023/day/1/input"),
+        Err(FetchError::MissingToken)
+    ));
+}
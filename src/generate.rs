@@ -0,0 +1,162 @@
+//! Random puzzle-shaped inputs of any size, to stress and benchmark the solutions beyond the
+//! official inputs
+
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+use crate::{day20, day24, day25, day5};
+
+/// SplitMix64, small and seedable so the same seed always gives the same input
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range`, which must not be empty
+    pub fn range(&mut self, range: Range<i64>) -> i64 {
+        let len = range.end.abs_diff(range.start);
+        range.start.wrapping_add((self.next_u64() % len) as i64)
+    }
+
+    /// A number in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// `count` distinct names of `len` lowercase letters, none of them in `reserved`
+    pub fn names(&mut self, count: usize, len: u32, reserved: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = (0..26_usize.pow(len))
+            .map(|mut n| {
+                let mut name = String::new();
+                for _ in 0..len {
+                    name.push((b'a' + (n % 26) as u8) as char);
+                    n /= 26;
+                }
+                name
+            })
+            .filter(|name| !reserved.contains(&name.as_str()))
+            .collect();
+        self.shuffle(&mut names);
+        names.truncate(count);
+        names
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    /// Days without a generator
+    Unsupported(u32),
+    /// Sizes the day's input can't have, like more components than 3 letter names or more
+    /// counters than a u64 answer has room for
+    Size { day: u32, min: usize, max: usize },
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GenerateError::Unsupported(day) => write!(f, "day {day} has no input generator"),
+            GenerateError::Size { day, min, max } => {
+                write!(f, "day {day} inputs have a size from {min} to {max}")
+            }
+        }
+    }
+}
+
+impl Error for GenerateError {}
+
+struct Generator {
+    day: u32,
+    sizes: Range<usize>,
+    generate: fn(usize, &mut Rng) -> String,
+}
+
+const GENERATORS: &[Generator] = &[
+    Generator {
+        day: 5,
+        sizes: 1..100_000,
+        generate: day5::generate,
+    },
+    Generator {
+        day: 20,
+        sizes: 1..day20::MAX_COUNTERS + 1,
+        generate: day20::generate,
+    },
+    Generator {
+        day: 24,
+        sizes: 3..100_000,
+        generate: day24::generate,
+    },
+    Generator {
+        day: 25,
+        sizes: 10..26 * 26 * 26 + 1,
+        generate: day25::generate,
+    },
+];
+
+/// An input for `day` that parses like a real one, `size` is what the day has most of: the ranges
+/// per map for day 5, the counters for day 20, the hailstones for day 24 and the components for
+/// day 25
+pub fn generate(day: u32, size: usize, seed: u64) -> Result<String, GenerateError> {
+    let generator = GENERATORS
+        .iter()
+        .find(|generator| generator.day == day)
+        .ok_or(GenerateError::Unsupported(day))?;
+    if !generator.sizes.contains(&size) {
+        return Err(GenerateError::Size {
+            day,
+            min: generator.sizes.start,
+            max: generator.sizes.end - 1,
+        });
+    }
+    Ok((generator.generate)(size, &mut Rng::new(seed)))
+}
+
+#[test]
+fn same_seed_same_input() {
+    for Generator { day, sizes, .. } in GENERATORS {
+        let input = generate(*day, sizes.start, 7).unwrap();
+        assert_eq!(generate(*day, sizes.start, 7).unwrap(), input);
+        assert_ne!(generate(*day, sizes.start, 8).unwrap(), input);
+    }
+}
+
+#[test]
+fn sizes() {
+    assert_eq!(
+        generate(20, 6, 0),
+        Err(GenerateError::Size {
+            day: 20,
+            min: 1,
+            max: 5
+        })
+    );
+    assert_eq!(
+        generate(1, 10, 0).unwrap_err().to_string(),
+        "day 1 has no input generator"
+    );
+}
+
+#[test]
+fn names() {
+    let names = Rng::new(0).names(26 * 26, 2, &["rx"]);
+    assert_eq!(names.len(), 26 * 26 - 1);
+    assert!(!names.contains(&"rx".to_string()));
+    assert!(names.iter().all(|name| name.len() == 2));
+}
@@ -0,0 +1,65 @@
+//! Solutions to [Advent of Code 2023](https://adventofcode.com/2023).
+//!
+//! Every `dayN` module has a `DayN` type implementing [`solution::Solution`], which parses the
+//! puzzle input once, reporting malformed input as a [`parse::ParseError`], and solves both parts
+//! from it, as well as `part1`/`part2` functions taking the raw input. [`registry::DAYS`] lists all
+//! of them for running days generically, the other modules hold the input store, answer checking
+//! and the Advent of Code client the `aoc-2023` binary is built from.
+//!
+//! ```
+//! use aoc_2023::day6::{self, Day6};
+//! use aoc_2023::solution::Solution;
+//!
+//! let input = "Time:      7  15   30\nDistance:  9  40  200";
+//! let races = Day6.parse(input)?;
+//! assert_eq!(Day6.part1(&races), 288);
+//! assert_eq!(Day6.part2(&races), Some(71503));
+//! assert_eq!(day6::part2(input), 71503);
+//! # Ok::<(), aoc_2023::parse::ParseError>(())
+//! ```
+
+#[cfg(feature = "fs")]
+pub mod answers;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day2;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+#[cfg(feature = "fs")]
+pub mod fetch;
+pub mod generate;
+pub mod parse;
+pub mod registry;
+#[cfg(feature = "fs")]
+pub mod scaffold;
+pub mod solution;
+#[cfg(feature = "fs")]
+pub mod store;
+#[cfg(feature = "fs")]
+pub mod submit;
+pub mod utils;
+pub mod visualize;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -1,152 +1,433 @@
-#![feature(test)]
-
-extern crate core;
-
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod utils;
-
-use std::fs::File;
-use std::io::{ErrorKind, Write};
-use std::path::PathBuf;
-use std::{env, fs};
-
-use anyhow::Result;
-use chrono::{DateTime, Datelike, FixedOffset, TimeZone, Utc};
-use curl::easy::Easy;
-
-const TOKEN_ENV: &str = "TOKEN";
-
-fn aoc_now() -> DateTime<FixedOffset> {
-    FixedOffset::west_opt(18_000)
-        .unwrap()
-        .from_utc_datetime(&Utc::now().naive_utc())
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+
+use aoc_2023::answers::{Answers, Check};
+#[cfg(feature = "bench")]
+use aoc_2023::bench::{render_table, write_csv, write_json, BenchConfig, Timing};
+use aoc_2023::fetch::{make_day, offline_from_env, CurlFetch, Fetch};
+use aoc_2023::generate::generate;
+use aoc_2023::registry::{self, Day};
+use aoc_2023::scaffold::new_day;
+use aoc_2023::store::{InputKey, InputStore, DEFAULT_VARIANT, YEAR};
+use aoc_2023::submit::{submit, Submissions, Verdict};
+use aoc_2023::utils::parallel::par_map;
+
+const SRC_DIR: &str = "./src/";
+
+#[derive(Parser)]
+#[command(about = "Advent of Code 2023 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Never touch the network, only use inputs that are already present
+    #[arg(long, global = true)]
+    offline: bool,
+    /// Root of the input store, laid out as {year}/day{day}/{variant}.txt [default: $AOC_INPUT_DIR or ./inputs]
+    #[arg(long, global = true)]
+    input_dir: Option<PathBuf>,
 }
 
-fn latest_aoc_year_day() -> (i32, u32) {
-    let now = aoc_now();
-    if now.month() != 12 {
-        println!("not AoC yet, returning last day for last year");
-        (now.year() - 1, 25u32)
-    } else if now.day() > 25 {
-        (now.year(), 25u32)
-    } else {
-        (now.year(), now.day())
+#[derive(Subcommand)]
+enum Command {
+    /// Run the solutions, all days and parts unless narrowed down
+    Run {
+        /// Day to run
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: Option<u32>,
+        /// Part to run
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=2))]
+        part: Option<u32>,
+        /// Stored input variant to use, e.g. `example` or another account's name
+        #[arg(long, default_value = DEFAULT_VARIANT)]
+        variant: String,
+        /// Input file to use instead of one from the input store
+        #[arg(short, long, requires = "day", conflicts_with = "variant")]
+        input: Option<PathBuf>,
+        /// Store the answers as the known good ones for the input instead of checking them
+        #[arg(long, conflicts_with = "input")]
+        record: bool,
+        /// Also draw the day's board with what the solutions found, in the terminal or to an
+        /// .svg or .png file
+        #[arg(long, requires = "day", value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+        visualize: Option<PathBuf>,
+        /// Also write the graph the day's solutions work on to this file in Graphviz DOT
+        #[arg(long, requires = "day", value_name = "FILE")]
+        dot: Option<PathBuf>,
+        /// Side of a cell in pixels when drawing to a file
+        #[arg(long, requires = "visualize", default_value_t = 8, value_parser = clap::value_parser!(u32).range(1..))]
+        cell_size: u32,
+    },
+    /// Time parsing and both parts of the solutions and print a table
+    #[cfg(feature = "bench")]
+    Bench {
+        /// Day to time, all days if not given
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: Option<u32>,
+        /// Stored input variant to use
+        #[arg(long, default_value = DEFAULT_VARIANT)]
+        variant: String,
+        /// Untimed runs of every stage before measuring
+        #[arg(long, default_value_t = 1)]
+        warmup: u32,
+        /// Timed runs of every stage
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+        reps: u32,
+        /// Also write the timings to this file as JSON
+        #[arg(long)]
+        json: Option<PathBuf>,
+        /// Also write the timings to this file as CSV
+        #[arg(long)]
+        csv: Option<PathBuf>,
+    },
+    /// Submit the answer for a part computed from the default input
+    Submit {
+        /// Day to submit
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: u32,
+        /// Part to submit
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=2))]
+        part: u32,
+    },
+    /// Write a random input shaped like a day's puzzle input, to stdout unless told otherwise
+    Generate {
+        /// Day to generate an input for
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: u32,
+        /// How big the input gets, what is counted depends on the day
+        #[arg(short, long)]
+        size: usize,
+        /// Seed for the random numbers, the same seed gives the same input
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// File to write the input to
+        #[arg(short, long, conflicts_with = "variant")]
+        output: Option<PathBuf>,
+        /// Store the input as this variant, to use it with `run` and `bench`
+        #[arg(long)]
+        variant: Option<String>,
+    },
+    /// Create the module for a day from src/template.rs and register it
+    NewDay {
+        /// Day to create
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: u32,
+    },
+}
+
+/// Reads `path`, or else the stored variant. A missing default input is downloaded first with
+/// `client`, `None` when offline
+fn read_input(
+    store: &InputStore,
+    client: Option<&mut (dyn Fetch + '_)>,
+    day: u32,
+    variant: &str,
+    path: Option<&PathBuf>,
+) -> Result<String> {
+    match path {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("Could not read input file {}", path.display())),
+        None => {
+            let key = InputKey::with_variant(YEAR, day, variant);
+            store.read_or_fetch(&key, client).with_context(|| {
+                let available = store.variants(YEAR, day).unwrap_or_default();
+                format!(
+                    "Could not read {}, stored variants: [{}]",
+                    store.path(&key).display(),
+                    available.join(", ")
+                )
+            })
+        }
     }
 }
 
-fn make_day(year: i32, day: u32) -> Result<()> {
-    let mut day_dir = PathBuf::from("./src/");
-    day_dir.push(format!("day{day}"));
+/// Parses the input of a day and solves the parts, all of them unless `part` is given
+fn solve_day(day: &Day, part: Option<u32>, input: &str) -> Result<Vec<(u32, Option<String>)>> {
+    let parts = match part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+    let results = day
+        .solution
+        .run(input, &parts)
+        .with_context(|| format!("Could not parse the input of day {}", day.day))?;
+    Ok(parts.into_iter().zip(results).collect())
+}
 
-    let url = format!("https://adventofcode.com/{year}/day/{day}/input");
+/// Prints the solved parts of a day, checking or recording the answers when the input came from
+/// the store. Returns how many answers did not match the recorded ones
+fn report_day(
+    store: &InputStore,
+    day: &Day,
+    part: Option<u32>,
+    solved: Vec<(u32, Option<String>)>,
+    variant: Option<&str>,
+    record: bool,
+) -> Result<usize> {
+    let mut answers = match variant {
+        Some(_) => Answers::load(store, YEAR, day.day)?,
+        None => Answers::default(),
+    };
+    let mut mismatches = 0;
 
-    match fs::create_dir(&day_dir) {
-        Ok(_) => (),
-        Err(error) => match error.kind() {
-            ErrorKind::AlreadyExists => (),
-            other_error => {
-                panic!("Problem creating directory: {other_error:?}")
+    for (p, answer) in solved {
+        let answer = match answer {
+            Some(answer) => answer,
+            None if part.is_none() => continue,
+            None => bail!("Day {} has no part {}", day.day, p),
+        };
+        let status = match variant {
+            Some(variant) if record => {
+                answers.set(variant, p, &answer);
+                " (recorded)".to_string()
             }
-        },
+            Some(variant) => match answers.check(variant, p, &answer) {
+                Check::Unknown => String::new(),
+                Check::Correct => " (ok)".to_string(),
+                Check::Wrong { expected } => {
+                    mismatches += 1;
+                    format!(" (MISMATCH, {} has {expected})", answers.path().display())
+                }
+            },
+            None => String::new(),
+        };
+        println!("day {} part {}: {}{}", day.day, p, answer, status);
     }
 
-    let mut input_path = day_dir.clone();
-    input_path.push("input.txt");
-
-    if !input_path.exists() {
-        let mut file = File::create(&input_path)?;
-
-        let mut easy = Easy::new();
-        let token = env::var(TOKEN_ENV)?;
-        easy.useragent("https://github.com/areeh")?;
-        easy.cookie(&format!("session={token}")).unwrap();
-        easy.url(&url).unwrap();
-        easy.write_function(move |data| {
-            file.write_all(data).unwrap();
-            Ok(data.len())
-        })
-        .unwrap();
-        easy.get(true).unwrap();
-        easy.perform()
-            .unwrap_or_else(|_| panic!("Encountered error when performing request to {:?}", &url));
-
-        if easy.response_code().unwrap() != 200 {
-            panic!(
-                "Got response code {} with url {}",
-                easy.response_code().unwrap(),
-                url
-            );
-        };
+    if record {
+        answers.save()?;
     }
+    Ok(mismatches)
+}
 
-    let mut rs_path = day_dir.clone();
-    rs_path.push("mod.rs");
+/// Solves every day, all at once with the `parallel` feature, and reports them in order
+fn run_all_days(
+    store: &InputStore,
+    mut client: Option<&mut dyn Fetch>,
+    part: Option<u32>,
+    variant: &str,
+    record: bool,
+) -> Result<usize> {
+    let inputs = registry::DAYS
+        .iter()
+        .map(|day| read_input(store, client.as_deref_mut(), day.day, variant, None))
+        .collect::<Result<Vec<_>>>()?;
+    let days: Vec<_> = registry::DAYS.iter().zip(&inputs).collect();
+    let solved = par_map(&days, |(day, input)| solve_day(day, part, input));
+    registry::DAYS
+        .iter()
+        .zip(solved)
+        .try_fold(0, |acc, (day, solved)| {
+            Ok(acc + report_day(store, day, part, solved?, Some(variant), record)?)
+        })
+}
 
-    if !rs_path.exists() {
-        let template = PathBuf::from("./src/template.rs");
-        let _ = File::create(&rs_path)?;
-        std::fs::copy(template, rs_path)?;
+/// Draws the scene of a day to the terminal, or to `target` unless that is `-`
+fn visualize_day(day: &Day, input: &str, target: &Path, cell_size: u32) -> Result<()> {
+    let scene = day
+        .solution
+        .visualize(input)
+        .with_context(|| format!("Could not parse the input of day {}", day.day))?
+        .ok_or_else(|| anyhow!("Day {} has nothing to visualize", day.day))?;
+    if target == Path::new("-") {
+        println!("{}", scene.to_ansi());
+    } else {
+        scene
+            .save(target, cell_size as usize)
+            .with_context(|| format!("Could not write {}", target.display()))?;
+        println!("Wrote {}", target.display());
     }
-
     Ok(())
 }
 
-fn make_until_today() -> Result<()> {
-    let (year, day) = latest_aoc_year_day();
-    (1..day + 1).try_for_each(|x| make_day(year, x))
+#[cfg(feature = "bench")]
+fn bench_days(
+    store: &InputStore,
+    mut client: Option<&mut dyn Fetch>,
+    days: &[&Day],
+    variant: &str,
+    config: &BenchConfig,
+) -> Result<Vec<Timing>> {
+    let mut timings = Vec::new();
+    for day in days {
+        let input = read_input(store, client.as_deref_mut(), day.day, variant, None)?;
+        let stages = day
+            .solution
+            .bench(&input, config)
+            .with_context(|| format!("Could not parse the input of day {}", day.day))?;
+        for (stage, stats) in stages {
+            timings.push(Timing::new(day.day, stage, &stats));
+        }
+    }
+    Ok(timings)
+}
+
+fn submit_day(store: &InputStore, client: &mut dyn Fetch, day: u32, part: u32) -> Result<()> {
+    let day = registry::get(day).ok_or_else(|| anyhow!("Day {day} is not solved"))?;
+    let input = read_input(store, Some(&mut *client), day.day, DEFAULT_VARIANT, None)?;
+    let answer = day
+        .solution
+        .run(&input, &[part])
+        .with_context(|| format!("Could not parse the input of day {}", day.day))?
+        .remove(0);
+    let answer = answer.ok_or_else(|| anyhow!("Day {} has no part {}", day.day, part))?;
+
+    let mut submissions = Submissions::load(store, YEAR, day.day)?;
+    let verdict = submit(
+        client,
+        &mut submissions,
+        YEAR,
+        day.day,
+        part,
+        &answer,
+        Utc::now(),
+    )?;
+    println!("day {} part {}: {} is {}", day.day, part, answer, verdict);
+
+    if verdict == Verdict::Right {
+        let mut answers = Answers::load(store, YEAR, day.day)?;
+        answers.set(DEFAULT_VARIANT, part, &answer);
+        answers.save()?;
+    }
+    Ok(())
 }
 
 fn main() -> Result<()> {
-    make_until_today()?;
-    day1::main()?;
-    day2::main()?;
-    day3::main()?;
-    day4::main()?;
-    day5::main()?;
-    day6::main()?;
-    day7::main()?;
-    day8::main()?;
-    day9::main()?;
-    day10::main()?;
-    day11::main()?;
-    day12::main()?;
-    day13::main()?;
-    day14::main()?;
-    day15::main()?;
-    day16::main()?;
-    day17::main()?;
-    day18::main()?;
-    day19::main()?;
-    day20::main()?;
-    day21::main()?;
-    day22::main()?;
-    day23::main()?;
-    day24::main()?;
-    day25::main()?;
+    let cli = Cli::parse();
+    let store = cli
+        .input_dir
+        .as_ref()
+        .map_or_else(InputStore::from_env, InputStore::new);
+    let offline = cli.offline || offline_from_env();
+    let mut client = CurlFetch::from_env();
+    let client: Option<&mut dyn Fetch> = match offline {
+        true => None,
+        false => Some(&mut client),
+    };
 
+    let mismatches = match cli.command {
+        Some(Command::Run {
+            day,
+            part,
+            variant,
+            input,
+            record,
+            visualize,
+            dot,
+            cell_size,
+        }) => match day {
+            Some(day) => {
+                let day = registry::get(day).ok_or_else(|| anyhow!("Day {day} is not solved"))?;
+                let text = read_input(&store, client, day.day, &variant, input.as_ref())?;
+                let mismatches = report_day(
+                    &store,
+                    day,
+                    part,
+                    solve_day(day, part, &text)?,
+                    input.is_none().then_some(variant.as_str()),
+                    record,
+                )?;
+                if let Some(target) = visualize {
+                    visualize_day(day, &text, &target, cell_size)?;
+                }
+                if let Some(path) = dot {
+                    let dot = day
+                        .solution
+                        .export_dot(&text)
+                        .with_context(|| format!("Could not parse the input of day {}", day.day))?
+                        .ok_or_else(|| anyhow!("Day {} has no graph", day.day))?;
+                    fs::write(&path, dot)
+                        .with_context(|| format!("Could not write {}", path.display()))?;
+                    println!("Wrote {}", path.display());
+                }
+                mismatches
+            }
+            None => run_all_days(&store, client, part, &variant, record)?,
+        },
+        #[cfg(feature = "bench")]
+        Some(Command::Bench {
+            day,
+            variant,
+            warmup,
+            reps,
+            json,
+            csv,
+        }) => {
+            let days = match day {
+                Some(day) => {
+                    vec![registry::get(day).ok_or_else(|| anyhow!("Day {day} is not solved"))?]
+                }
+                None => registry::DAYS.iter().collect(),
+            };
+            let config = BenchConfig { warmup, reps };
+            let timings = bench_days(&store, client, &days, &variant, &config)?;
+            print!("{}", render_table(&timings));
+            if let Some(path) = json {
+                write_json(&path, &timings)
+                    .with_context(|| format!("Could not write {}", path.display()))?;
+            }
+            if let Some(path) = csv {
+                write_csv(&path, &timings)
+                    .with_context(|| format!("Could not write {}", path.display()))?;
+            }
+            0
+        }
+        Some(Command::Submit { day, part }) => {
+            let Some(client) = client else {
+                bail!("Cannot submit answers in offline mode");
+            };
+            submit_day(&store, client, day, part)?;
+            0
+        }
+        Some(Command::Generate {
+            day,
+            size,
+            seed,
+            output,
+            variant,
+        }) => {
+            let input = generate(day, size, seed)?;
+            match (output, variant) {
+                (Some(path), _) => {
+                    fs::write(&path, input)
+                        .with_context(|| format!("Could not write {}", path.display()))?;
+                    println!("Wrote {}", path.display());
+                }
+                (None, Some(variant)) => {
+                    if variant == DEFAULT_VARIANT {
+                        bail!("Not overwriting the {DEFAULT_VARIANT} variant with a generated one");
+                    }
+                    let key = InputKey::with_variant(YEAR, day, &variant);
+                    let path = store.path(&key);
+                    store
+                        .write(&key, input)
+                        .with_context(|| format!("Could not write {}", path.display()))?;
+                    println!("Wrote {}", path.display());
+                }
+                (None, None) => println!("{input}"),
+            }
+            0
+        }
+        Some(Command::NewDay { day }) => {
+            let src_dir = Path::new(SRC_DIR);
+            let created = match client {
+                None => new_day(src_dir, &store, YEAR, day)?,
+                Some(client) => make_day(src_dir, &store, YEAR, day, client)?,
+            };
+            match created {
+                true => println!("Created {}", src_dir.join(format!("day{day}")).display()),
+                false => println!("Day {day} already exists"),
+            }
+            0
+        }
+        None => run_all_days(&store, client, None, DEFAULT_VARIANT, false)?,
+    };
+
+    if mismatches > 0 {
+        bail!("{mismatches} answer(s) did not match the recorded ones");
+    }
     Ok(())
 }
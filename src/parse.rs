@@ -0,0 +1,251 @@
+//! The error every day's parser returns, with helpers for the line based inputs most days have
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(test)]
+use crate::utils::grid::parse_grid;
+use crate::utils::grid::ParseGridError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// Text that doesn't have the expected shape
+    Format {
+        expected: &'static str,
+        found: String,
+    },
+    /// Text that should be a number
+    Number(String),
+    /// A name or symbol the puzzle doesn't have, like a colour or a module type
+    Unknown { what: &'static str, found: String },
+    /// A section or value that isn't there
+    Missing(&'static str),
+    /// A line of a board that is not as long as the first one
+    Ragged { expected: usize, found: usize },
+    /// A character a board can't have, columns count from 1
+    InvalidCell { column: usize, cell: char },
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseErrorKind::Format { expected, found } => {
+                write!(f, "expected {expected}, found '{found}'")
+            }
+            ParseErrorKind::Number(found) => write!(f, "invalid number '{found}'"),
+            ParseErrorKind::Unknown { what, found } => write!(f, "unknown {what} '{found}'"),
+            ParseErrorKind::Missing(what) => write!(f, "missing {what}"),
+            ParseErrorKind::Ragged { expected, found } => {
+                write!(
+                    f,
+                    "expected {expected} cells like the first line, found {found}"
+                )
+            }
+            ParseErrorKind::InvalidCell { column, cell } => {
+                write!(f, "invalid cell {cell:?} in column {column}")
+            }
+        }
+    }
+}
+
+/// Why an input could not be parsed, lines count from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// `None` for errors about the input as a whole
+    pub line: Option<usize>,
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind) -> Self {
+        Self { line: None, kind }
+    }
+
+    pub fn format(expected: &'static str, found: &str) -> Self {
+        Self::new(ParseErrorKind::Format {
+            expected,
+            found: found.to_string(),
+        })
+    }
+
+    pub fn number(found: &str) -> Self {
+        Self::new(ParseErrorKind::Number(found.to_string()))
+    }
+
+    pub fn unknown(what: &'static str, found: impl ToString) -> Self {
+        Self::new(ParseErrorKind::Unknown {
+            what,
+            found: found.to_string(),
+        })
+    }
+
+    pub fn missing(what: &'static str) -> Self {
+        Self::new(ParseErrorKind::Missing(what))
+    }
+
+    /// Places an error from a single line, errors that already know their line keep it
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line.get_or_insert(line);
+        self
+    }
+
+    /// Moves the line of an error from a part of the input that starts after `lines` others
+    pub fn offset(mut self, lines: usize) -> Self {
+        self.line = Some(self.line.unwrap_or(1) + lines);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.kind),
+            None => self.kind.fmt(f),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<ParseGridError> for ParseError {
+    fn from(err: ParseGridError) -> Self {
+        match err {
+            ParseGridError::Empty => Self::missing("board"),
+            ParseGridError::Ragged {
+                line,
+                expected,
+                found,
+            } => Self::new(ParseErrorKind::Ragged { expected, found }).at_line(line),
+            ParseGridError::InvalidCell { line, column, cell } => {
+                Self::new(ParseErrorKind::InvalidCell { column, cell }).at_line(line)
+            }
+        }
+    }
+}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// Parses every line with `parse`, the first error gets the number of its line
+pub fn parse_lines<'a, T>(
+    input: &'a str,
+    mut parse: impl FnMut(&'a str) -> ParseResult<T>,
+) -> ParseResult<Vec<T>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse(line).map_err(|err| err.at_line(i + 1)))
+        .collect()
+}
+
+/// Parses every block of lines separated by an empty line with `parse`. Lines of errors count
+/// from the start of the block and are moved to count from the start of the input, errors
+/// without a line are placed on the first line of their block
+pub fn parse_blocks<'a, T>(
+    input: &'a str,
+    mut parse: impl FnMut(&'a str) -> ParseResult<T>,
+) -> ParseResult<Vec<T>> {
+    let (mut rest, mut start, mut parsed) = (Some(input), 0, Vec::new());
+    while let Some(text) = rest {
+        let (block, after) = match split_block(text) {
+            Some((block, after)) => (block, Some(after)),
+            None => (text, None),
+        };
+        parsed.push(parse(block).map_err(|err| err.offset(start))?);
+        start += block.lines().count() + 1;
+        rest = after;
+    }
+    Ok(parsed)
+}
+
+/// The lines before and after the first empty line, without the line ending of the last line
+/// before it. Lines may end in `\n` or `\r\n`
+pub fn split_block(input: &str) -> Option<(&str, &str)> {
+    let mut end = 0;
+    for line in input.split_inclusive('\n') {
+        if matches!(line, "\n" | "\r\n") {
+            let block = &input[..end];
+            let block = block.strip_suffix('\n').unwrap_or(block);
+            let block = block.strip_suffix('\r').unwrap_or(block);
+            return Some((block, &input[end + line.len()..]));
+        }
+        end += line.len();
+    }
+    None
+}
+
+/// A number, surrounding whitespace is allowed
+pub fn number<T: FromStr>(text: &str) -> ParseResult<T> {
+    text.trim().parse().map_err(|_| ParseError::number(text))
+}
+
+/// Whitespace separated numbers
+pub fn numbers<T: FromStr>(text: &str) -> ParseResult<Vec<T>> {
+    text.split_whitespace().map(number).collect()
+}
+
+/// The text before and after `separator`, or an error saying `text` should look like `expected`
+pub fn split_once<'a>(
+    text: &'a str,
+    separator: &str,
+    expected: &'static str,
+) -> ParseResult<(&'a str, &'a str)> {
+    text.split_once(separator)
+        .ok_or_else(|| ParseError::format(expected, text))
+}
+
+#[test]
+fn line_numbers() {
+    let parsed = parse_lines("1 2\n3 4", numbers::<u32>);
+    assert_eq!(parsed, Ok(vec![vec![1, 2], vec![3, 4]]));
+
+    let err = parse_lines("1 2\n3 x\n5 y", numbers::<u32>).unwrap_err();
+    assert_eq!(err, ParseError::number("x").at_line(2));
+    assert_eq!(err.to_string(), "line 2: invalid number 'x'");
+
+    let blocks = "a: 1\nb: 2\n\nc: 3\nd 4";
+    let err = parse_blocks(blocks, |block| {
+        parse_lines(block, |line| {
+            let (_, value) = split_once(line, ": ", "[name]: [value]")?;
+            number::<u32>(value)
+        })
+    })
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 5: expected [name]: [value], found 'd 4'"
+    );
+    let err = parse_blocks(blocks, |block| match block.starts_with('c') {
+        true => Err(ParseError::missing("seeds")),
+        false => Ok(()),
+    })
+    .unwrap_err();
+    assert_eq!(err.to_string(), "line 4: missing seeds");
+
+    let err = parse_blocks("#.\n##\n\n#.\n#", |block| {
+        Ok(parse_grid(block, |c| "#.".contains(c).then_some(c))?)
+    })
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 5: expected 2 cells like the first line, found 1"
+    );
+}
+
+#[test]
+fn crlf_blocks() {
+    assert_eq!(split_block("a\nb\n\nc\n"), Some(("a\nb", "c\n")));
+    assert_eq!(
+        split_block("a\r\nb\r\n\r\nc\r\n"),
+        Some(("a\r\nb", "c\r\n"))
+    );
+    assert_eq!(split_block("\r\nc"), Some(("", "c")));
+    assert_eq!(split_block("a\r\nb"), None);
+
+    let blocks = |input: &str| parse_blocks(input, |block| parse_lines(block, numbers::<u32>));
+    let parsed = Ok(vec![vec![vec![1, 2], vec![3]], vec![vec![4]]]);
+    assert_eq!(blocks("1 2\n3\n\n4\n"), parsed);
+    assert_eq!(blocks("1 2\r\n3\r\n\r\n4\r\n"), parsed);
+    let err = blocks("1\r\n\r\n2\r\nx").unwrap_err();
+    assert_eq!(err.to_string(), "line 4: invalid number 'x'");
+}
@@ -0,0 +1,122 @@
+use crate::solution::Runner;
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9,
+};
+
+pub struct Day {
+    pub day: u32,
+    pub solution: &'static dyn Runner,
+}
+
+pub const DAYS: &[Day] = &[
+    Day {
+        day: 1,
+        solution: &day1::Day1,
+    },
+    Day {
+        day: 2,
+        solution: &day2::Day2,
+    },
+    Day {
+        day: 3,
+        solution: &day3::Day3,
+    },
+    Day {
+        day: 4,
+        solution: &day4::Day4,
+    },
+    Day {
+        day: 5,
+        solution: &day5::Day5,
+    },
+    Day {
+        day: 6,
+        solution: &day6::Day6,
+    },
+    Day {
+        day: 7,
+        solution: &day7::Day7,
+    },
+    Day {
+        day: 8,
+        solution: &day8::Day8,
+    },
+    Day {
+        day: 9,
+        solution: &day9::Day9,
+    },
+    Day {
+        day: 10,
+        solution: &day10::Day10,
+    },
+    Day {
+        day: 11,
+        solution: &day11::Day11,
+    },
+    Day {
+        day: 12,
+        solution: &day12::Day12,
+    },
+    Day {
+        day: 13,
+        solution: &day13::Day13,
+    },
+    Day {
+        day: 14,
+        solution: &day14::Day14,
+    },
+    Day {
+        day: 15,
+        solution: &day15::Day15,
+    },
+    Day {
+        day: 16,
+        solution: &day16::Day16,
+    },
+    Day {
+        day: 17,
+        solution: &day17::Day17,
+    },
+    Day {
+        day: 18,
+        solution: &day18::Day18,
+    },
+    Day {
+        day: 19,
+        solution: &day19::Day19,
+    },
+    Day {
+        day: 20,
+        solution: &day20::Day20,
+    },
+    Day {
+        day: 21,
+        solution: &day21::Day21 {
+            part1_steps: day21::P1_STEPS,
+            part2_steps: day21::P2_STEPS,
+        },
+    },
+    Day {
+        day: 22,
+        solution: &day22::Day22,
+    },
+    Day {
+        day: 23,
+        solution: &day23::Day23,
+    },
+    Day {
+        day: 24,
+        solution: &day24::Day24 {
+            bound: day24::TASK_BOUND,
+        },
+    },
+    Day {
+        day: 25,
+        solution: &day25::Day25,
+    },
+];
+
+pub fn get(day: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
@@ -0,0 +1,233 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::store::InputStore;
+
+pub const TEMPLATE_FILE: &str = "template.rs";
+pub const LIB_FILE: &str = "lib.rs";
+pub const REGISTRY_FILE: &str = "registry.rs";
+
+const MAX_WIDTH: usize = 100;
+
+fn unescape_html(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(['<', '&']) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with('<') {
+            // Only markup like <em> shows up inside the blocks, drop it
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else {
+            let entity = rest.find(';').map(|end| (&rest[..=end], &rest[end + 1..]));
+            let (replacement, after) = match entity {
+                Some(("&lt;", after)) => ("<", after),
+                Some(("&gt;", after)) => (">", after),
+                Some(("&amp;", after)) => ("&", after),
+                Some(("&quot;", after)) => ("\"", after),
+                Some(("&#39;", after)) => ("'", after),
+                _ => ("&", &rest[1..]),
+            };
+            result.push_str(replacement);
+            rest = after;
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The first `<pre><code>` block following a mention of an example, or the first one at all
+pub fn scrape_example(html: &str) -> Option<String> {
+    const OPEN: &str = "<pre><code>";
+    const CLOSE: &str = "</code></pre>";
+
+    let after_mention = html
+        .find("example")
+        .and_then(|mention| html[mention..].find(OPEN).map(|start| mention + start));
+    let start = after_mention.or_else(|| html.find(OPEN))? + OPEN.len();
+    let end = start + html[start..].find(CLOSE)?;
+    Some(unescape_html(&html[start..end]).trim_end().to_string())
+}
+
+/// Fills in `{{day}}` and `{{example}}`, the latter inside a string literal
+pub fn render_template(template: &str, day: u32, example: Option<&str>) -> String {
+    let example = example
+        .unwrap_or_default()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    template
+        .replace("{{day}}", &day.to_string())
+        .replace("{{example}}", &example)
+}
+
+fn declared_module(line: &str) -> Option<&str> {
+    line.strip_prefix("pub mod ")?.strip_suffix(';')
+}
+
+/// Adds `pub mod day{day};` among the other module declarations, keeping them sorted
+fn register_module(lib_rs: &str, day: u32) -> Option<String> {
+    let module = format!("day{day}");
+    let declaration = format!("pub mod {module};");
+    let mut lines = lib_rs.lines().collect::<Vec<_>>();
+    if lines.contains(&declaration.as_str()) {
+        return None;
+    }
+
+    let last = lines
+        .iter()
+        .rposition(|line| declared_module(line).is_some())?;
+    let position = lines
+        .iter()
+        .position(|line| declared_module(line).is_some_and(|name| name > module.as_str()))
+        .unwrap_or(last + 1);
+    lines.insert(position, &declaration);
+    Some(lines.join("\n") + "\n")
+}
+
+fn render_use_list(names: &[String]) -> String {
+    let single = format!("use crate::{{{}}};", names.join(", "));
+    if single.len() <= MAX_WIDTH {
+        return single;
+    }
+
+    let mut lines = vec!["use crate::{".to_string()];
+    let mut line = String::new();
+    for name in names {
+        if !line.is_empty() && line.len() + name.len() + 2 > MAX_WIDTH {
+            lines.push(std::mem::take(&mut line));
+        }
+        if line.is_empty() {
+            line.push_str("   ");
+        }
+        line.push(' ');
+        line.push_str(name);
+        line.push(',');
+    }
+    lines.push(line);
+    lines.push("};".to_string());
+    lines.join("\n")
+}
+
+/// Imports the day module and appends an entry for its `Day{day}` solution to `DAYS`
+fn register_day(registry_rs: &str, day: u32) -> Option<String> {
+    let module = format!("day{day}");
+    if registry_rs.contains(&format!("        day: {day},\n")) {
+        return None;
+    }
+
+    let use_start = registry_rs.find("use crate::{")?;
+    let use_end = use_start + registry_rs[use_start..].find("};")? + "};".len();
+    let mut names = registry_rs[use_start + "use crate::{".len()..use_end - "};".len()]
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    if !names.contains(&module) {
+        names.push(module.clone());
+        names.sort();
+    }
+
+    let days_start = registry_rs.find("pub const DAYS")?;
+    let days_end = days_start + registry_rs[days_start..].find("\n];")? + 1;
+    let entry = format!(
+        "    Day {{\n        day: {day},\n        solution: &{module}::Day{day},\n    }},\n"
+    );
+
+    Some(format!(
+        "{}{}{}{}{}",
+        &registry_rs[..use_start],
+        render_use_list(&names),
+        &registry_rs[use_end..days_end],
+        entry,
+        &registry_rs[days_end..]
+    ))
+}
+
+/// Renders `src/day{day}/mod.rs` from the template, with the example scraped from the cached
+/// puzzle description if there is one, and registers the module in the runner.
+/// Returns false if the module already existed
+pub fn new_day(src_dir: &Path, store: &InputStore, year: i32, day: u32) -> io::Result<bool> {
+    let day_dir = src_dir.join(format!("day{day}"));
+    let rs_path = day_dir.join("mod.rs");
+    if rs_path.exists() {
+        return Ok(false);
+    }
+
+    let example = match store.read_puzzle(year, day) {
+        Ok(html) => scrape_example(&html),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err),
+    };
+    let template = fs::read_to_string(src_dir.join(TEMPLATE_FILE))?;
+    fs::create_dir_all(&day_dir)?;
+    fs::write(
+        &rs_path,
+        render_template(&template, day, example.as_deref()),
+    )?;
+
+    let update = |file: &str, register: fn(&str, u32) -> Option<String>| {
+        let path = src_dir.join(file);
+        match register(&fs::read_to_string(&path)?, day) {
+            Some(updated) => fs::write(path, updated),
+            None => Ok(()),
+        }
+    };
+    update(LIB_FILE, register_module)?;
+    update(REGISTRY_FILE, register_day)?;
+    Ok(true)
+}
+
+#[test]
+fn scrape_puzzle_example() {
+    let html = "<article class=\"day-desc\"><h2>--- Day 3: Gear Ratios ---</h2><p>The engine schematic consists of numbers:</p><pre><code>not this</code></pre><p>Here is an example engine schematic:</p>
+<pre><code>467..114..
+...*......
+..35..&lt;33.
+<em>617</em>*&amp;....
+</code></pre>
+<p>In this schematic, two numbers are <em>not</em> part numbers.</p>";
+    assert_eq!(
+        scrape_example(html).unwrap(),
+        "467..114..\n...*......\n..35..<33.\n617*&...."
+    );
+    assert_eq!(
+        scrape_example("<pre><code>a\nb\n</code></pre>").as_deref(),
+        Some("a\nb")
+    );
+    assert_eq!(scrape_example("<p>no code</p>"), None);
+}
+
+#[test]
+fn render_and_register() {
+    let template = "fn task() {\n    let input = &read_input_to_string({{day}}).unwrap();\n    let example = \"{{example}}\";\n}\n";
+    assert_eq!(
+        render_template(template, 26, Some("a\"b\\c")),
+        "fn task() {\n    let input = &read_input_to_string(26).unwrap();\n    let example = \"a\\\"b\\\\c\";\n}\n"
+    );
+
+    let lib_rs = "//! Docs\n\npub mod answers;\n#[cfg(feature = \"bench\")]\npub mod bench;\npub mod day1;\npub mod day2;\npub mod store;\n";
+    assert_eq!(
+        register_module(lib_rs, 10).unwrap(),
+        "//! Docs\n\npub mod answers;\n#[cfg(feature = \"bench\")]\npub mod bench;\npub mod day1;\npub mod day10;\npub mod day2;\npub mod store;\n"
+    );
+    assert_eq!(register_module(lib_rs, 2), None);
+
+    let registry_rs = fs::read_to_string("src/registry.rs").unwrap();
+    assert_eq!(register_day(&registry_rs, 25), None);
+    let updated = register_day(&registry_rs, 26).unwrap();
+    assert!(updated.contains("day24, day25, day26, day3,"));
+    assert!(updated.lines().all(|line| line.len() <= MAX_WIDTH));
+    assert!(updated.ends_with(
+        "    Day {\n        day: 26,\n        solution: &day26::Day26,\n    },\n];\n\npub fn get(day: u32) -> Option<&'static Day> {\n    DAYS.iter().find(|d| d.day == day)\n}\n"
+    ));
+    assert_eq!(
+        updated
+            .replace(
+                "    Day {\n        day: 26,\n        solution: &day26::Day26,\n    },\n",
+                ""
+            )
+            .replace(", day26", ""),
+        registry_rs
+    );
+}
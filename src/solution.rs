@@ -0,0 +1,93 @@
+use std::fmt;
+
+#[cfg(feature = "bench")]
+use crate::bench::{measure, BenchConfig, Stage, Stats};
+use crate::parse::ParseError;
+use crate::visualize::Scene;
+
+/// A day's puzzle, the input is parsed once and shared by both parts.
+/// Parameters that differ between the examples and the real input, like day21's step count,
+/// are fields of the implementing type
+pub trait Solution {
+    type Input<'a>;
+    type Answer1: fmt::Display;
+    type Answer2: fmt::Display;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input<'a>, ParseError>;
+
+    fn part1(&self, input: &Self::Input<'_>) -> Self::Answer1;
+
+    /// `None` for days without a part 2 solution
+    fn part2(&self, _input: &Self::Input<'_>) -> Option<Self::Answer2> {
+        None
+    }
+
+    /// The board with what the solutions found drawn on top, `None` for days without a board
+    fn scene(&self, _input: &Self::Input<'_>) -> Option<Scene> {
+        None
+    }
+
+    /// The graph the solutions work on in Graphviz DOT, `None` for days without one
+    fn dot(&self, _input: &Self::Input<'_>) -> Option<String> {
+        None
+    }
+}
+
+/// Answer type for a part that has no solution
+pub enum Unsolved {}
+
+impl fmt::Display for Unsolved {
+    fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+        match *self {}
+    }
+}
+
+/// Object safe view of a [`Solution`], which is what the registry stores
+pub trait Runner: Sync {
+    /// Parses `input` once and solves the requested parts, `None` for parts without a solution
+    fn run(&self, input: &str, parts: &[u32]) -> Result<Vec<Option<String>>, ParseError>;
+
+    /// Parses `input` and draws the day's [`Solution::scene`]
+    fn visualize(&self, input: &str) -> Result<Option<Scene>, ParseError>;
+
+    /// Parses `input` and renders the day's [`Solution::dot`]
+    fn export_dot(&self, input: &str) -> Result<Option<String>, ParseError>;
+
+    /// Times parsing and each part separately, the parts reuse a single parsed input
+    #[cfg(feature = "bench")]
+    fn bench(&self, input: &str, config: &BenchConfig) -> Result<Vec<(Stage, Stats)>, ParseError>;
+}
+
+impl<S: Solution + Sync> Runner for S {
+    fn run(&self, input: &str, parts: &[u32]) -> Result<Vec<Option<String>>, ParseError> {
+        let input = self.parse(input)?;
+        Ok(parts
+            .iter()
+            .map(|part| match part {
+                1 => Some(self.part1(&input).to_string()),
+                2 => self.part2(&input).map(|answer| answer.to_string()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    fn visualize(&self, input: &str) -> Result<Option<Scene>, ParseError> {
+        Ok(self.scene(&self.parse(input)?))
+    }
+
+    fn export_dot(&self, input: &str) -> Result<Option<String>, ParseError> {
+        Ok(self.dot(&self.parse(input)?))
+    }
+
+    #[cfg(feature = "bench")]
+    fn bench(&self, input: &str, config: &BenchConfig) -> Result<Vec<(Stage, Stats)>, ParseError> {
+        let (parsed, parse) = measure(config, || self.parse(input));
+        let parsed = parsed?;
+        let (_, part1) = measure(config, || self.part1(&parsed));
+        let mut timings = vec![(Stage::Parse, parse), (Stage::Part1, part1)];
+        if let (Some(_), part2) = measure(config, || self.part2(&parsed)) {
+            timings.push((Stage::Part2, part2));
+        }
+        Ok(timings)
+    }
+}
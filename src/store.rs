@@ -0,0 +1,167 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::fetch::{fetch_input, Fetch, FetchError};
+
+pub const YEAR: i32 = 2023;
+pub const INPUT_DIR_ENV: &str = "AOC_INPUT_DIR";
+pub const DEFAULT_INPUT_DIR: &str = "./inputs";
+pub const DEFAULT_VARIANT: &str = "input";
+pub const PUZZLE_FILE: &str = "puzzle.html";
+
+/// Identifies one input file, `variant` separates e.g. examples and other accounts' inputs
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InputKey {
+    pub year: i32,
+    pub day: u32,
+    pub variant: String,
+}
+
+impl InputKey {
+    pub fn new(year: i32, day: u32) -> Self {
+        Self::with_variant(year, day, DEFAULT_VARIANT)
+    }
+
+    pub fn with_variant(year: i32, day: u32, variant: &str) -> Self {
+        Self {
+            year,
+            day,
+            variant: variant.to_string(),
+        }
+    }
+}
+
+/// Inputs stored as `{root}/{year}/day{day}/{variant}.txt`
+#[derive(Debug, Clone)]
+pub struct InputStore {
+    root: PathBuf,
+}
+
+impl InputStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(env::var(INPUT_DIR_ENV).unwrap_or_else(|_| DEFAULT_INPUT_DIR.to_string()))
+    }
+
+    pub fn day_dir(&self, year: i32, day: u32) -> PathBuf {
+        self.root.join(year.to_string()).join(format!("day{day}"))
+    }
+
+    pub fn path(&self, key: &InputKey) -> PathBuf {
+        self.day_dir(key.year, key.day)
+            .join(format!("{}.txt", key.variant))
+    }
+
+    pub fn contains(&self, key: &InputKey) -> bool {
+        self.path(key).exists()
+    }
+
+    pub fn read(&self, key: &InputKey) -> io::Result<String> {
+        fs::read_to_string(self.path(key))
+    }
+
+    /// Reads an input, downloading it first if it is a missing default variant and there is a
+    /// `client`. Other variants and offline runs, without a client, only read what is stored
+    pub fn read_or_fetch(
+        &self,
+        key: &InputKey,
+        client: Option<&mut (dyn Fetch + '_)>,
+    ) -> Result<String, FetchError> {
+        if let Some(client) = client.filter(|_| key.variant == DEFAULT_VARIANT) {
+            fetch_input(self, key.year, key.day, client)?;
+        }
+        Ok(self.read(key)?)
+    }
+
+    pub fn write(&self, key: &InputKey, contents: impl AsRef<[u8]>) -> io::Result<()> {
+        fs::create_dir_all(self.day_dir(key.year, key.day))?;
+        fs::write(self.path(key), contents)
+    }
+
+    /// The cached puzzle description
+    pub fn puzzle_path(&self, year: i32, day: u32) -> PathBuf {
+        self.day_dir(year, day).join(PUZZLE_FILE)
+    }
+
+    pub fn read_puzzle(&self, year: i32, day: u32) -> io::Result<String> {
+        fs::read_to_string(self.puzzle_path(year, day))
+    }
+
+    pub fn write_puzzle(&self, year: i32, day: u32, contents: impl AsRef<[u8]>) -> io::Result<()> {
+        fs::create_dir_all(self.day_dir(year, day))?;
+        fs::write(self.puzzle_path(year, day), contents)
+    }
+
+    /// All variants stored for a day, sorted by name
+    pub fn variants(&self, year: i32, day: u32) -> io::Result<Vec<String>> {
+        let mut variants = Vec::new();
+        for entry in fs::read_dir(self.day_dir(year, day))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "txt") {
+                if let Some(stem) = path.file_stem() {
+                    variants.push(stem.to_string_lossy().into_owned());
+                }
+            }
+        }
+        variants.sort();
+        Ok(variants)
+    }
+}
+
+#[test]
+fn store_round_trip() {
+    let root = env::temp_dir().join(format!("aoc-2023-store-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let store = InputStore::new(&root);
+
+    let input = InputKey::new(2022, 7);
+    let example = InputKey::with_variant(2022, 7, "example");
+    assert!(!store.contains(&input));
+
+    store.write(&input, "$ cd /\n").unwrap();
+    store.write(&example, "$ ls\n").unwrap();
+    assert_eq!(store.path(&input), root.join("2022/day7/input.txt"));
+    assert_eq!(store.read(&input).unwrap(), "$ cd /\n");
+    assert_eq!(store.read(&example).unwrap(), "$ ls\n");
+    assert_eq!(store.variants(2022, 7).unwrap(), ["example", "input"]);
+    assert!(store.read(&InputKey::new(2023, 7)).is_err());
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn fetch_missing_inputs_only() {
+    use crate::fetch::LocalFetch;
+
+    let root = env::temp_dir().join(format!("aoc-2023-read-or-fetch-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let (store, site) = (InputStore::new(root.join("inputs")), root.join("site"));
+    fs::create_dir_all(site.join("2023/day/9")).unwrap();
+    fs::write(site.join("2023/day/9/input"), "0 3 6\n").unwrap();
+    let mut client = LocalFetch::new(&site);
+
+    let (input, example) = (
+        InputKey::new(2023, 9),
+        InputKey::with_variant(2023, 9, "example"),
+    );
+    assert!(store.read_or_fetch(&input, None).is_err());
+    assert!(store.read_or_fetch(&example, Some(&mut client)).is_err());
+    assert!(!store.contains(&input));
+
+    assert_eq!(
+        store.read_or_fetch(&input, Some(&mut client)).unwrap(),
+        "0 3 6\n"
+    );
+    fs::remove_dir_all(&site).unwrap();
+    assert_eq!(
+        store.read_or_fetch(&input, Some(&mut client)).unwrap(),
+        "0 3 6\n"
+    );
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,402 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::fetch::{Fetch, FetchError};
+use crate::store::InputStore;
+
+pub const SUBMISSIONS_FILE: &str = "submissions.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+    /// Submitted during a cooldown, the answer was not checked
+    Wait,
+    /// The part was already solved, the answer was not checked
+    AlreadySolved,
+}
+
+impl Verdict {
+    fn is_wrong(self) -> bool {
+        matches!(self, Verdict::Wrong | Verdict::TooHigh | Verdict::TooLow)
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            Verdict::Right => "right",
+            Verdict::Wrong => "wrong",
+            Verdict::TooHigh => "too high",
+            Verdict::TooLow => "too low",
+            Verdict::Wait => "not checked, submitted too recently",
+            Verdict::AlreadySolved => "not checked, part is already solved",
+        };
+        f.write_str(text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub verdict: Verdict,
+    pub cooldown: Option<Duration>,
+}
+
+/// `4m 53s` style durations, as in "You have 4m 53s left to wait"
+fn parse_left_to_wait(text: &str) -> Option<Duration> {
+    let (_, rest) = text.split_once("You have ")?;
+    let (left, _) = rest.split_once(" left to wait")?;
+    left.split_whitespace()
+        .try_fold(Duration::zero(), |acc, part| {
+            let (value, unit) = part.split_at(part.len() - 1);
+            let value = value.parse().ok()?;
+            match unit {
+                "h" => Some(acc + Duration::hours(value)),
+                "m" => Some(acc + Duration::minutes(value)),
+                "s" => Some(acc + Duration::seconds(value)),
+                _ => None,
+            }
+        })
+}
+
+/// As in "Please wait one minute" or "please wait 5 minutes before trying again"
+fn parse_please_wait(text: &str) -> Option<Duration> {
+    let (_, rest) = text.split_once("lease wait ")?;
+    let (amount, rest) = rest.split_once(' ')?;
+    let amount = match amount {
+        "one" => 1,
+        amount => amount.parse().ok()?,
+    };
+    rest.starts_with("minute")
+        .then(|| Duration::minutes(amount))
+}
+
+/// Parses the page returned after posting an answer
+pub fn parse_response(html: &str) -> Option<Response> {
+    let verdict = if html.contains("That's the right answer") {
+        Verdict::Right
+    } else if html.contains("That's not the right answer") {
+        if html.contains("your answer is too high") {
+            Verdict::TooHigh
+        } else if html.contains("your answer is too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Wrong
+        }
+    } else if html.contains("You gave an answer too recently") {
+        Verdict::Wait
+    } else if html.contains("You don't seem to be solving the right level") {
+        Verdict::AlreadySolved
+    } else {
+        return None;
+    };
+
+    let cooldown = match verdict {
+        Verdict::Wait => parse_left_to_wait(html),
+        _ if verdict.is_wrong() => parse_please_wait(html),
+        _ => None,
+    };
+    Some(Response { verdict, cooldown })
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    Fetch(FetchError),
+    Io(io::Error),
+    Parse {
+        path: PathBuf,
+        err: toml::de::Error,
+    },
+    Serialize(toml::ser::Error),
+    UnknownResponse(String),
+    /// The answer, or a bound given for an earlier answer, is known to be wrong
+    KnownWrong(Submission),
+    AlreadySolved(Submission),
+    Cooldown {
+        until: DateTime<Utc>,
+    },
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubmitError::Fetch(err) => write!(f, "{err}"),
+            SubmitError::Io(err) => write!(f, "io error: {err}"),
+            SubmitError::Parse { path, err } => {
+                write!(f, "could not parse {}: {err}", path.display())
+            }
+            SubmitError::Serialize(err) => write!(f, "could not serialize submissions: {err}"),
+            SubmitError::UnknownResponse(body) => write!(f, "unrecognized response: {body}"),
+            SubmitError::KnownWrong(previous) => write!(
+                f,
+                "not submitting, {} was {} at {}",
+                previous.answer, previous.verdict, previous.at
+            ),
+            SubmitError::AlreadySolved(previous) => write!(
+                f,
+                "not submitting, part {} was solved with {} at {}",
+                previous.part, previous.answer, previous.at
+            ),
+            SubmitError::Cooldown { until } => {
+                write!(f, "not submitting, wait until {until} before trying again")
+            }
+        }
+    }
+}
+
+impl Error for SubmitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SubmitError::Fetch(err) => Some(err),
+            SubmitError::Io(err) => Some(err),
+            SubmitError::Parse { err, .. } => Some(err),
+            SubmitError::Serialize(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<FetchError> for SubmitError {
+    fn from(err: FetchError) -> Self {
+        SubmitError::Fetch(err)
+    }
+}
+
+impl From<io::Error> for SubmitError {
+    fn from(err: io::Error) -> Self {
+        SubmitError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submission {
+    pub part: u32,
+    pub answer: String,
+    pub verdict: Verdict,
+    pub at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Log {
+    #[serde(default, rename = "submission")]
+    submissions: Vec<Submission>,
+}
+
+/// Every answer posted for a day, stored next to its inputs as `submissions.toml`
+#[derive(Debug)]
+pub struct Submissions {
+    path: PathBuf,
+    log: Log,
+}
+
+impl Submissions {
+    pub fn load(store: &InputStore, year: i32, day: u32) -> Result<Self, SubmitError> {
+        let path = store.day_dir(year, day).join(SUBMISSIONS_FILE);
+        let log = match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).map_err(|err| SubmitError::Parse {
+                path: path.clone(),
+                err,
+            })?,
+            Err(err) if err.kind() == ErrorKind::NotFound => Log::default(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self { path, log })
+    }
+
+    pub fn save(&self) -> Result<(), SubmitError> {
+        let contents = toml::to_string(&self.log).map_err(SubmitError::Serialize)?;
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(fs::write(&self.path, contents)?)
+    }
+
+    fn solved(&self, part: u32) -> Option<&Submission> {
+        self.log
+            .submissions
+            .iter()
+            .find(|s| s.part == part && s.verdict == Verdict::Right)
+    }
+
+    /// An earlier submission showing `answer` is wrong, either the same answer or a bound
+    fn known_wrong(&self, part: u32, answer: &str) -> Option<&Submission> {
+        let value = answer.parse::<i64>().ok();
+        self.log.submissions.iter().find(|s| {
+            if s.part != part || !s.verdict.is_wrong() {
+                return false;
+            }
+            match (value, s.answer.parse::<i64>().ok(), s.verdict) {
+                _ if s.answer == answer => true,
+                (Some(value), Some(high), Verdict::TooHigh) => value >= high,
+                (Some(value), Some(low), Verdict::TooLow) => value <= low,
+                _ => false,
+            }
+        })
+    }
+
+    fn wait_until(&self) -> Option<DateTime<Utc>> {
+        self.log
+            .submissions
+            .iter()
+            .filter_map(|s| s.wait_until)
+            .max()
+    }
+}
+
+/// Posts `answer` unless the log already shows it is wrong, the part is solved or a cooldown
+/// is running. The response is added to the log, which is saved before returning
+pub fn submit(
+    client: &mut dyn Fetch,
+    submissions: &mut Submissions,
+    year: i32,
+    day: u32,
+    part: u32,
+    answer: &str,
+    now: DateTime<Utc>,
+) -> Result<Verdict, SubmitError> {
+    if let Some(solved) = submissions.solved(part) {
+        return match solved.answer == answer {
+            true => Ok(Verdict::Right),
+            false => Err(SubmitError::AlreadySolved(solved.clone())),
+        };
+    }
+    if let Some(previous) = submissions.known_wrong(part, answer) {
+        return Err(SubmitError::KnownWrong(previous.clone()));
+    }
+    if let Some(until) = submissions.wait_until().filter(|until| *until > now) {
+        return Err(SubmitError::Cooldown { until });
+    }
+
+    let body = client.post(
+        &format!("/{year}/day/{day}/answer"),
+        &format!("level={part}&answer={answer}"),
+    )?;
+    let body = String::from_utf8_lossy(&body);
+    let response =
+        parse_response(&body).ok_or_else(|| SubmitError::UnknownResponse(body.into()))?;
+
+    submissions.log.submissions.push(Submission {
+        part,
+        answer: answer.to_string(),
+        verdict: response.verdict,
+        at: now,
+        wait_until: response.cooldown.map(|cooldown| now + cooldown),
+    });
+    submissions.save()?;
+    Ok(response.verdict)
+}
+
+#[test]
+fn parse_responses() {
+    let right = "<article><p>That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer to restoring snow operations.</p></article>";
+    let too_high = "<article><p>That's not the right answer; your answer is too high.  If you're stuck, make sure you're using the full input data; there are also some general tips on the <a href=\"/2023/about\">about page</a>, or you can ask for hints on the <a href=\"https://www.reddit.com/r/adventofcode/\" target=\"_blank\">subreddit</a>.  Please wait one minute before trying again. <a href=\"/2023/day/5\">[Return to Day 5]</a></p></article>";
+    let wrong = "<article><p>That's not the right answer.  Because you have guessed incorrectly 6 times on this puzzle, please wait 5 minutes before trying again.</p></article>";
+    let wait = "<article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 23s left to wait. <a href=\"/2023/day/5\">[Return to Day 5]</a></p></article>";
+    let solved = "<article><p>You don't seem to be solving the right level.  Did you already complete it? <a href=\"/2023/day/5\">[Return to Day 5]</a></p></article>";
+
+    assert_eq!(
+        parse_response(right),
+        Some(Response {
+            verdict: Verdict::Right,
+            cooldown: None
+        })
+    );
+    assert_eq!(
+        parse_response(too_high),
+        Some(Response {
+            verdict: Verdict::TooHigh,
+            cooldown: Some(Duration::minutes(1))
+        })
+    );
+    assert_eq!(
+        parse_response(wrong),
+        Some(Response {
+            verdict: Verdict::Wrong,
+            cooldown: Some(Duration::minutes(5))
+        })
+    );
+    assert_eq!(
+        parse_response(wait),
+        Some(Response {
+            verdict: Verdict::Wait,
+            cooldown: Some(Duration::seconds(83))
+        })
+    );
+    assert_eq!(
+        parse_response(solved).unwrap().verdict,
+        Verdict::AlreadySolved
+    );
+    assert_eq!(parse_response("<html>502 Bad Gateway</html>"), None);
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn submit_against_mock_server() {
+    use crate::fetch::{mock_server, CurlFetch};
+
+    let root = std::env::temp_dir().join(format!("aoc-2023-submit-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let store = InputStore::new(&root);
+    let (url, server) = mock_server(vec![
+        (
+            200,
+            "<p>That's not the right answer; your answer is too high.  Please wait one minute before trying again.</p>".to_string(),
+        ),
+        (200, "<p>That's the right answer!</p>".to_string()),
+    ]);
+    let mut client = CurlFetch::new(Some("abc".to_string()), url);
+    let start = DateTime::parse_from_rfc3339("2023-12-05T05:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+
+    let mut submissions = Submissions::load(&store, 2023, 5).unwrap();
+    let verdict = submit(&mut client, &mut submissions, 2023, 5, 1, "500", start).unwrap();
+    assert_eq!(verdict, Verdict::TooHigh);
+
+    // Known wrong answers and cooldowns are enforced without posting, also after reloading
+    let mut submissions = Submissions::load(&store, 2023, 5).unwrap();
+    let later = start + Duration::seconds(30);
+    assert!(matches!(
+        submit(&mut client, &mut submissions, 2023, 5, 1, "500", later),
+        Err(SubmitError::KnownWrong(_))
+    ));
+    assert!(matches!(
+        submit(&mut client, &mut submissions, 2023, 5, 1, "600", later),
+        Err(SubmitError::KnownWrong(_))
+    ));
+    assert!(matches!(
+        submit(&mut client, &mut submissions, 2023, 5, 1, "35", later),
+        Err(SubmitError::Cooldown { .. })
+    ));
+
+    let later = start + Duration::minutes(2);
+    let verdict = submit(&mut client, &mut submissions, 2023, 5, 1, "35", later).unwrap();
+    assert_eq!(verdict, Verdict::Right);
+    assert_eq!(
+        submit(&mut client, &mut submissions, 2023, 5, 1, "35", later).unwrap(),
+        Verdict::Right
+    );
+    assert!(matches!(
+        submit(&mut client, &mut submissions, 2023, 5, 1, "36", later),
+        Err(SubmitError::AlreadySolved(_))
+    ));
+
+    let requests = server.join().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].starts_with("POST /2023/day/5/answer HTTP/1.1\r\n"));
+    assert!(requests[0].ends_with("level=1&answer=500"));
+    assert!(requests[1].ends_with("level=1&answer=35"));
+
+    fs::remove_dir_all(&root).unwrap();
+}
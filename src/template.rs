@@ -1,43 +1,51 @@
-extern crate test;
+//! [Day {{day}}](https://adventofcode.com/2023/day/{{day}})
 
 use itertools::Itertools;
 
-#[cfg(test)]
-use test::Bencher;
+use crate::parse::ParseError;
+use crate::solution::Solution;
+#[cfg(all(test, feature = "fs"))]
+use crate::utils::{assert_answer, read_input_to_string};
 
-use crate::utils::read_input_to_string;
+pub struct Day{{day}};
 
-fn part1(input: &str) {}
+impl Solution for Day{{day}} {
+    type Input<'a> = &'a str;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-fn part2(input: &str) {}
+    fn parse<'a>(&self, input: &'a str) -> Result<&'a str, ParseError> {
+        Ok(input)
+    }
 
-pub fn main() -> std::io::Result<()> {
-    let input = &read_input_to_string(5)?;
-    dbg!(part1(input));
-    dbg!(part2(input));
+    fn part1(&self, input: &&str) -> usize {
+        todo!()
+    }
 
-    Ok(())
+    fn part2(&self, input: &&str) -> Option<usize> {
+        todo!()
+    }
+}
+
+pub fn part1(input: &str) -> usize {
+    Day{{day}}.part1(&Day{{day}}.parse(input).unwrap())
+}
+
+pub fn part2(input: &str) -> usize {
+    Day{{day}}.part2(&Day{{day}}.parse(input).unwrap()).unwrap()
 }
 
 #[test]
 fn example() {
-    let input = "";
-    assert_eq!(part1(input), ());
-    assert_eq!(part2(input), ());
+    let input = "{{example}}";
+    assert_eq!(part1(input), 0);
+    assert_eq!(part2(input), 0);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn task() {
-    let input = &read_input_to_string(5).unwrap();
-    assert_eq!(part1(input), ());
-    assert_eq!(part2(input), ());
-}
-
-#[bench]
-fn task_bench(b: &mut Bencher) {
-    b.iter(|| {
-        let input = &read_input_to_string(5).unwrap();
-        part1(input);
-        part2(input);
-    })
+    let input = &read_input_to_string({{day}}).unwrap();
+    assert_answer({{day}}, 1, part1(input));
+    assert_answer({{day}}, 2, part2(input));
 }
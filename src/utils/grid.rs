@@ -0,0 +1,525 @@
+//! Positions, directions and a 2D grid indexed by them
+
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Sub, SubAssign};
+use std::str::FromStr;
+
+use ndarray::{s, Array2};
+
+use crate::utils::{pretty_string, rot270, rot90};
+
+/// A cell on a grid, `x` grows to the right and `y` downwards. Coordinates are signed so a
+/// position can be off the grid, like on the infinitely repeating map of day 21
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    pub x: isize,
+    pub y: isize,
+}
+
+const NEIGHBORS8: [Position; 8] = [
+    Position::new(-1, -1),
+    Position::new(0, -1),
+    Position::new(1, -1),
+    Position::new(-1, 0),
+    Position::new(1, 0),
+    Position::new(-1, 1),
+    Position::new(0, 1),
+    Position::new(1, 1),
+];
+
+impl Position {
+    pub const ORIGIN: Position = Position::new(0, 0);
+
+    pub const fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+
+    /// From an `ndarray` `(row, column)` index
+    pub fn from_index((row, column): (usize, usize)) -> Self {
+        Self::new(column as isize, row as isize)
+    }
+
+    /// The `(row, column)` index into an `ndarray`, `None` for negative coordinates
+    pub fn to_index(self) -> Option<(usize, usize)> {
+        Some((usize::try_from(self.y).ok()?, usize::try_from(self.x).ok()?))
+    }
+
+    pub fn manhattan(self, other: Position) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// `n` steps towards `dir`
+    pub fn moved(self, dir: Direction, n: isize) -> Self {
+        self + dir.offset() * n
+    }
+
+    /// The positions up, left, down and right of this one
+    pub fn neighbors4(self) -> impl Iterator<Item = Position> {
+        Direction::ALL.into_iter().map(move |dir| self + dir)
+    }
+
+    /// The 4 neighbors and the 4 diagonal ones, row by row
+    pub fn neighbors8(self) -> impl Iterator<Item = Position> {
+        NEIGHBORS8.into_iter().map(move |offset| self + offset)
+    }
+}
+
+impl Add for Position {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Position {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Position {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Position {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<isize> for Position {
+    type Output = Self;
+
+    fn mul(self, factor: isize) -> Self::Output {
+        Self::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Add<Direction> for Position {
+    type Output = Position;
+
+    fn add(self, dir: Direction) -> Position {
+        self + dir.offset()
+    }
+}
+
+impl AddAssign<Direction> for Position {
+    fn add_assign(&mut self, dir: Direction) {
+        *self = *self + dir;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Left,
+    Down,
+    Right,
+}
+
+impl Direction {
+    /// Counterclockwise, starting with up
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Left,
+        Direction::Down,
+        Direction::Right,
+    ];
+
+    /// The position one step in this direction from the origin
+    pub fn offset(self) -> Position {
+        match self {
+            Direction::Up => Position::new(0, -1),
+            Direction::Left => Position::new(-1, 0),
+            Direction::Down => Position::new(0, 1),
+            Direction::Right => Position::new(1, 0),
+        }
+    }
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Left => Direction::Right,
+            Direction::Down => Direction::Up,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// Turned 90° clockwise
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Left => Direction::Up,
+            Direction::Down => Direction::Left,
+            Direction::Right => Direction::Down,
+        }
+    }
+
+    /// Turned 90° counterclockwise
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn is_perpendicular(self, other: Direction) -> bool {
+        other != self && other != self.opposite()
+    }
+}
+
+/// A rectangular grid of cells, stored row by row in an [`Array2`] and indexed by [`Position`].
+/// Indexing panics outside the grid, [`Grid::get`] and [`Grid::step`] check the bounds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Array2<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(cells: Array2<T>) -> Self {
+        Self { cells }
+    }
+
+    pub fn from_elem(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(Array2::from_elem((height, width), value))
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.ncols()
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.nrows()
+    }
+
+    /// Position of the bottom right cell
+    pub fn bottom_right(&self) -> Position {
+        Position::new(self.width() as isize - 1, self.height() as isize - 1)
+    }
+
+    pub fn cells(&self) -> &Array2<T> {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut Array2<T> {
+        &mut self.cells
+    }
+
+    pub fn into_cells(self) -> Array2<T> {
+        self.cells
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        (0..self.width() as isize).contains(&pos.x) && (0..self.height() as isize).contains(&pos.y)
+    }
+
+    pub fn get(&self, pos: Position) -> Option<&T> {
+        self.cells.get(pos.to_index()?)
+    }
+
+    pub fn get_mut(&mut self, pos: Position) -> Option<&mut T> {
+        self.cells.get_mut(pos.to_index()?)
+    }
+
+    /// One step from `pos` towards `dir`, `None` if that leaves the grid
+    pub fn step(&self, pos: Position, dir: Direction) -> Option<Position> {
+        let next = pos + dir;
+        self.contains(next).then_some(next)
+    }
+
+    /// The 4-neighbourhood of `pos` that is inside the grid
+    pub fn neighbors4(&self, pos: Position) -> impl Iterator<Item = Position> + '_ {
+        pos.neighbors4().filter(|next| self.contains(*next))
+    }
+
+    /// The 8-neighbourhood of `pos` that is inside the grid
+    pub fn neighbors8(&self, pos: Position) -> impl Iterator<Item = Position> + '_ {
+        pos.neighbors8().filter(|next| self.contains(*next))
+    }
+
+    /// Maps `pos` onto the grid as if it repeated infinitely in every direction
+    pub fn wrap(&self, pos: Position) -> Position {
+        Position::new(
+            pos.x.rem_euclid(self.width() as isize),
+            pos.y.rem_euclid(self.height() as isize),
+        )
+    }
+
+    /// The cell at `pos` on the infinitely repeating grid
+    pub fn get_wrapped(&self, pos: Position) -> &T {
+        &self[self.wrap(pos)]
+    }
+
+    /// All cells with their positions, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.cells
+            .indexed_iter()
+            .map(|(ix, cell)| (Position::from_index(ix), cell))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Position, &mut T)> {
+        self.cells
+            .indexed_iter_mut()
+            .map(|(ix, cell)| (Position::from_index(ix), cell))
+    }
+
+    /// Position of the first cell, row by row, matching `predicate`
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<Position> {
+        self.iter()
+            .find_map(|(pos, cell)| predicate(cell).then_some(pos))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.cells.map(f))
+    }
+
+    /// Surrounded by a border of `width` cells set to `value`, which moves every cell by
+    /// `width` to the right and down
+    pub fn padded(&self, width: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        let mut cells =
+            Array2::from_elem((self.height() + 2 * width, self.width() + 2 * width), value);
+        cells
+            .slice_mut(s![
+                width..width + self.height(),
+                width..width + self.width()
+            ])
+            .assign(&self.cells);
+        Self::new(cells)
+    }
+
+    /// Rotates the grid counterclockwise in place, see [`rot90`]
+    pub fn rot90(&mut self) {
+        rot90(&mut self.cells);
+    }
+
+    /// Rotates the grid clockwise in place, see [`rot270`]
+    pub fn rot270(&mut self) {
+        rot270(&mut self.cells);
+    }
+}
+
+impl<T> From<Array2<T>> for Grid<T> {
+    fn from(cells: Array2<T>) -> Self {
+        Self::new(cells)
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Position) -> &T {
+        let (width, height) = (self.width(), self.height());
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside of the {width}x{height} grid"))
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, pos: Position) -> &mut T {
+        let (width, height) = (self.width(), self.height());
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside of the {width}x{height} grid"))
+    }
+}
+
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", pretty_string(&self.cells.view()))
+    }
+}
+
+/// Why a grid could not be parsed, lines and columns count from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseGridError {
+    Empty,
+    /// A line that is not as long as the first one
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A character the cell mapping rejected
+    InvalidCell {
+        line: usize,
+        column: usize,
+        cell: char,
+    },
+}
+
+impl fmt::Display for ParseGridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseGridError::Empty => write!(f, "the grid has no lines"),
+            ParseGridError::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line} has {found} cells, but the first line has {expected}"
+            ),
+            ParseGridError::InvalidCell { line, column, cell } => {
+                write!(f, "line {line}, column {column}: invalid cell {cell:?}")
+            }
+        }
+    }
+}
+
+impl Error for ParseGridError {}
+
+/// Parses lines of equal length into a grid, mapping every character with `cell`.
+/// Accepts `\n` and `\r\n` line endings and ignores trailing blank lines
+pub fn parse_grid<T>(
+    input: &str,
+    mut cell: impl FnMut(char) -> Option<T>,
+) -> Result<Grid<T>, ParseGridError> {
+    let mut lines = input
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<_>>();
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    let width = lines.first().ok_or(ParseGridError::Empty)?.chars().count();
+
+    let mut cells = Vec::with_capacity(lines.len() * width);
+    for (row, line) in lines.iter().enumerate() {
+        let found = line.chars().count();
+        if found != width {
+            return Err(ParseGridError::Ragged {
+                line: row + 1,
+                expected: width,
+                found,
+            });
+        }
+        for (column, c) in line.chars().enumerate() {
+            cells.push(cell(c).ok_or(ParseGridError::InvalidCell {
+                line: row + 1,
+                column: column + 1,
+                cell: c,
+            })?);
+        }
+    }
+
+    let cells = Array2::from_shape_vec((lines.len(), width), cells)
+        .expect("all lines have the same length");
+    Ok(Grid::new(cells))
+}
+
+impl FromStr for Grid<char> {
+    type Err = ParseGridError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parse_grid(input, Some)
+    }
+}
+
+#[test]
+fn grid_geometry() {
+    let mut grid: Grid<char> = "abc\ndef".parse().unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid[Position::new(2, 1)], 'f');
+    assert_eq!(grid.position(|c| *c == 'e'), Some(Position::new(1, 1)));
+    assert_eq!(grid.get(Position::new(-1, 0)), None);
+    assert_eq!(grid.get(Position::new(3, 0)), None);
+
+    let corner = Position::ORIGIN;
+    assert_eq!(grid.step(corner, Direction::Up), None);
+    assert_eq!(
+        grid.step(corner, Direction::Right),
+        Some(Position::new(1, 0))
+    );
+    assert_eq!(
+        grid.neighbors4(corner).collect::<Vec<_>>(),
+        [Position::new(0, 1), Position::new(1, 0)]
+    );
+    assert_eq!(grid.neighbors8(Position::new(1, 0)).count(), 5);
+    assert_eq!(Position::new(1, 1).neighbors8().count(), 8);
+
+    assert_eq!(grid.wrap(Position::new(-1, 5)), Position::new(2, 1));
+    assert_eq!(*grid.get_wrapped(Position::new(4, -2)), 'b');
+    assert_eq!(corner.moved(Direction::Down, 3), Position::new(0, 3));
+    assert_eq!(Position::new(-2, 3).manhattan(Position::new(1, 1)), 5);
+
+    let padded = grid.padded(1, '#');
+    assert_eq!(padded.to_string(), "#####\n#abc#\n#def#\n#####");
+    assert_eq!(padded[Position::new(1, 1)], 'a');
+
+    grid.rot90();
+    assert_eq!(grid.to_string(), "cf\nbe\nad");
+    grid.rot270();
+    assert_eq!(grid.to_string(), "abc\ndef");
+    grid[Position::new(0, 1)] = 'x';
+    assert_eq!(
+        grid.map(|c| c.is_alphabetic())
+            .iter()
+            .filter(|(_, c)| **c)
+            .count(),
+        6
+    );
+    assert_eq!(grid.to_string(), "abc\nxef");
+}
+
+#[test]
+fn directions() {
+    for dir in Direction::ALL {
+        assert_eq!(dir.turn_right().turn_left(), dir);
+        assert_eq!(dir.turn_right().turn_right(), dir.opposite());
+        assert_eq!(dir.offset() + dir.opposite().offset(), Position::ORIGIN);
+        assert!(dir.is_perpendicular(dir.turn_left()));
+        assert!(!dir.is_perpendicular(dir.opposite()));
+    }
+}
+
+#[test]
+fn parse_grids() {
+    let grid = parse_grid("12\r\n34\r\n\r\n  \n", |c| c.to_digit(10)).unwrap();
+    assert_eq!(grid.cells(), &ndarray::array![[1, 2], [3, 4]]);
+
+    #[derive(Debug, PartialEq)]
+    enum Tile {
+        Open,
+        Wall,
+    }
+    let tile = |c| match c {
+        '.' => Some(Tile::Open),
+        '#' => Some(Tile::Wall),
+        _ => None,
+    };
+    let grid = parse_grid(".#\n#.", tile).unwrap();
+    assert_eq!(grid[Position::new(1, 0)], Tile::Wall);
+
+    assert_eq!(
+        parse_grid(".#\n#.\n..#", tile),
+        Err(ParseGridError::Ragged {
+            line: 3,
+            expected: 2,
+            found: 3
+        })
+    );
+    let err = parse_grid(".#\n#x", tile).unwrap_err();
+    assert_eq!(
+        err,
+        ParseGridError::InvalidCell {
+            line: 2,
+            column: 2,
+            cell: 'x'
+        }
+    );
+    assert_eq!(err.to_string(), "line 2, column 2: invalid cell 'x'");
+    assert_eq!("\n\n".parse::<Grid<char>>(), Err(ParseGridError::Empty));
+}
@@ -0,0 +1,82 @@
+pub mod grid;
+pub mod parallel;
+pub mod search;
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use ndarray::{ArrayBase, ArrayView2, Axis, Ix2, RawData};
+
+#[cfg(all(test, feature = "fs"))]
+use crate::answers::{Answers, Check};
+#[cfg(all(test, feature = "fs"))]
+use crate::store::{InputKey, InputStore, DEFAULT_VARIANT, YEAR};
+
+#[cfg(all(test, feature = "fs"))]
+pub fn read_input_to_string(day: u32) -> std::io::Result<String> {
+    InputStore::from_env().read(&InputKey::new(YEAR, day))
+}
+
+/// Compares against the answer recorded for the default input, if there is one
+#[cfg(all(test, feature = "fs"))]
+pub fn assert_answer(day: u32, part: u32, answer: impl ToString) {
+    let answers = Answers::load(&InputStore::from_env(), YEAR, day).unwrap();
+    match answers.check(DEFAULT_VARIANT, part, &answer.to_string()) {
+        Check::Correct => (),
+        Check::Unknown => eprintln!(
+            "day {day} part {part}: no answer recorded in {}, not checked",
+            answers.path().display()
+        ),
+        Check::Wrong { expected } => panic!(
+            "day {day} part {part}: got {}, but {} has {expected}",
+            answer.to_string(),
+            answers.path().display()
+        ),
+    }
+}
+
+/// The board as text, one line per row
+pub fn pretty_string(arr: &ArrayView2<char>) -> String {
+    let mut result = String::new();
+    for row in arr.rows() {
+        for elem in row {
+            result.push(*elem);
+        }
+        result.push('\n');
+    }
+
+    result.trim_end().to_owned()
+}
+
+pub fn pretty_print(arr: &ArrayView2<char>) {
+    println!("{}", pretty_string(arr));
+}
+
+/// Rotates a 2D array counterclockwise in place like `numpy.rot90`, without copying the data.
+/// See <https://github.com/rust-ndarray/ndarray/issues/866>
+pub fn rot90<S>(arr: &mut ArrayBase<S, Ix2>)
+where
+    S: RawData,
+{
+    arr.swap_axes(0, 1);
+    arr.invert_axis(Axis(0));
+}
+
+/// Rotates a 2D array clockwise in place, without copying the data.
+/// See <https://github.com/rust-ndarray/ndarray/issues/866>
+pub fn rot270<S>(arr: &mut ArrayBase<S, Ix2>)
+where
+    S: RawData,
+{
+    arr.swap_axes(0, 1);
+    arr.invert_axis(Axis(1));
+}
+
+pub fn has_unique_elements<T>(iter: T) -> bool
+where
+    T: IntoIterator,
+    T::Item: Eq + Hash,
+{
+    let mut uniq = HashSet::new();
+    iter.into_iter().all(move |x| uniq.insert(x))
+}
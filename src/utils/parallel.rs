@@ -0,0 +1,26 @@
+//! Maps that are spread over threads with the `parallel` feature and run one item after the
+//! other without it, with the same results either way
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// `f` applied to every item, the results are in the order of `items`
+#[cfg(feature = "parallel")]
+pub fn par_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    items.par_iter().map(f).collect()
+}
+
+/// `f` applied to every item, the results are in the order of `items`
+#[cfg(not(feature = "parallel"))]
+pub fn par_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    items.iter().map(f).collect()
+}
+
+#[test]
+fn keeps_order() {
+    let items = (0..1000).collect::<Vec<u64>>();
+    assert_eq!(
+        par_map(&items, |n| n * n),
+        items.iter().map(|n| n * n).collect::<Vec<_>>()
+    );
+}
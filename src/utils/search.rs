@@ -0,0 +1,277 @@
+//! Breadth-first, Dijkstra and A* search over any state type, with successors given by a closure
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+use priority_queue::PriorityQueue;
+
+/// What a search does with a state it takes off its queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    /// Expand the successors of the state
+    Continue,
+    /// Keep the state but don't expand it, e.g. because it is as far as the search should go
+    Prune,
+    /// End the search at this state, which becomes the goal
+    Stop,
+}
+
+/// The states a search reached, with their cost from the closest start and the state they were
+/// reached from. Costs of visited states are final, states that were only queued when the search
+/// stopped may have a cheaper path
+#[derive(Debug, Clone)]
+pub struct Search<S, C> {
+    /// The state the search stopped at, if it did
+    pub goal: Option<S>,
+    /// How many states were taken off the queue
+    pub visited: usize,
+    costs: HashMap<S, C>,
+    parents: HashMap<S, S>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy> Search<S, C> {
+    fn new() -> Self {
+        Self {
+            goal: None,
+            visited: 0,
+            costs: HashMap::new(),
+            parents: HashMap::new(),
+        }
+    }
+
+    pub fn cost(&self, state: &S) -> Option<C> {
+        self.costs.get(state).copied()
+    }
+
+    pub fn goal_cost(&self) -> Option<C> {
+        self.cost(self.goal.as_ref()?)
+    }
+
+    /// How many distinct states were reached, including the ones still queued
+    pub fn discovered(&self) -> usize {
+        self.costs.len()
+    }
+
+    /// All reached states with their costs, in no particular order
+    pub fn reached(&self) -> impl Iterator<Item = (&S, C)> {
+        self.costs.iter().map(|(state, cost)| (state, *cost))
+    }
+
+    /// The states from a start to `state`, both included
+    pub fn path(&self, state: &S) -> Option<Vec<S>> {
+        if !self.costs.contains_key(state) {
+            return None;
+        }
+        let mut path = vec![state.clone()];
+        while let Some(parent) = self.parents.get(path.last().unwrap()) {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn goal_path(&self) -> Option<Vec<S>> {
+        self.path(self.goal.as_ref()?)
+    }
+}
+
+/// Breadth-first search from all `starts` at once, where the cost of a state is the number of
+/// steps from the closest start. `visit` sees every state once, in order of increasing cost
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut visit: impl FnMut(&S, usize) -> Visit,
+) -> Search<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if let Entry::Vacant(entry) = search.costs.entry(start.clone()) {
+            entry.insert(0);
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(state) = queue.pop_front() {
+        search.visited += 1;
+        let steps = search.costs[&state];
+        match visit(&state, steps) {
+            Visit::Continue => (),
+            Visit::Prune => continue,
+            Visit::Stop => {
+                search.goal = Some(state);
+                break;
+            }
+        }
+
+        for next in successors(&state) {
+            if let Entry::Vacant(entry) = search.costs.entry(next.clone()) {
+                entry.insert(steps + 1);
+                search.parents.insert(next.clone(), state.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    search
+}
+
+/// Shortest paths from all `starts` at once, `successors` gives the next states with the cost
+/// of the step to each of them. `visit` sees every state once, in order of increasing cost
+pub fn dijkstra<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    visit: impl FnMut(&S, C) -> Visit,
+) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(starts, successors, |_| C::default(), visit)
+}
+
+/// Like [`dijkstra`], but states are visited in order of their cost plus `heuristic`, which must
+/// never overestimate the remaining cost to the goal and never drop by more than a step costs
+pub fn astar<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut visit: impl FnMut(&S, C) -> Visit,
+) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut search = Search::new();
+    let mut queue = PriorityQueue::new();
+    let mut closed = HashSet::new();
+    for start in starts {
+        search.costs.insert(start.clone(), C::default());
+        let priority = heuristic(&start);
+        queue.push(start, Reverse(priority));
+    }
+
+    while let Some((state, _)) = queue.pop() {
+        search.visited += 1;
+        let cost = search.costs[&state];
+        closed.insert(state.clone());
+        match visit(&state, cost) {
+            Visit::Continue => (),
+            Visit::Prune => continue,
+            Visit::Stop => {
+                search.goal = Some(state);
+                break;
+            }
+        }
+
+        for (next, step) in successors(&state) {
+            if closed.contains(&next) {
+                continue;
+            }
+            let next_cost = cost + step;
+            if search.cost(&next).is_none_or(|known| next_cost < known) {
+                search.costs.insert(next.clone(), next_cost);
+                search.parents.insert(next.clone(), state.clone());
+                let priority = next_cost + heuristic(&next);
+                queue.push_increase(next, Reverse(priority));
+            }
+        }
+    }
+    search
+}
+
+#[cfg(test)]
+fn maze_successors<'a>(maze: &'a [&str]) -> impl Fn(&(usize, usize)) -> Vec<(usize, usize)> + 'a {
+    move |&(x, y)| {
+        [
+            (x + 1, y),
+            (x.wrapping_sub(1), y),
+            (x, y + 1),
+            (x, y.wrapping_sub(1)),
+        ]
+        .into_iter()
+        .filter(|&(x, y)| maze.get(y).and_then(|row| row.as_bytes().get(x)) == Some(&b'.'))
+        .collect()
+    }
+}
+
+#[test]
+fn breadth_first() {
+    let maze = ["..#....", ".##.##.", "....#..", "##.##.#", "......."];
+    let successors = maze_successors(&maze);
+
+    let goal = (6, 0);
+    let search = bfs([(0, 0)], &successors, |state, _| match *state == goal {
+        true => Visit::Stop,
+        false => Visit::Continue,
+    });
+    assert_eq!(search.goal_cost(), Some(10));
+    let path = search.goal_path().unwrap();
+    assert_eq!((path[0], path.len()), ((0, 0), 11));
+    assert!(path
+        .windows(2)
+        .all(|step| successors(&step[0]).contains(&step[1])));
+    assert!(search.visited <= search.discovered());
+
+    // Without a goal everything reachable is visited, pruning stops at a depth
+    let all = bfs([(0, 0)], &successors, |_, _| Visit::Continue);
+    assert_eq!((all.goal, all.visited), (None, 24));
+    let near = bfs([(0, 0)], &successors, |_, steps| match steps {
+        2 => Visit::Prune,
+        _ => Visit::Continue,
+    });
+    assert_eq!(near.reached().filter(|(_, steps)| *steps == 2).count(), 1);
+    assert_eq!(near.cost(&(3, 0)), None);
+
+    // Several starts at once
+    let both = bfs([(0, 0), (6, 4)], &successors, |_, _| Visit::Continue);
+    assert_eq!(both.cost(&(6, 0)), Some(6));
+}
+
+#[test]
+fn weighted() {
+    // Going around through 'b' is cheaper than the direct edge
+    let edges = |node: &char| match node {
+        'a' => vec![('b', 1), ('d', 10)],
+        'b' => vec![('c', 2)],
+        'c' => vec![('d', 3)],
+        _ => vec![],
+    };
+    let search = dijkstra(['a'], edges, |node, _| match node {
+        'd' => Visit::Stop,
+        _ => Visit::Continue,
+    });
+    assert_eq!(search.goal_cost(), Some(6));
+    assert_eq!(search.goal_path(), Some(vec!['a', 'b', 'c', 'd']));
+
+    let maze = ["..#....", ".##.##.", "....#..", "##.##.#", "......."];
+    let successors = maze_successors(&maze);
+    let weighted = |state: &(usize, usize)| {
+        successors(state)
+            .into_iter()
+            .map(|next| (next, 1))
+            .collect::<Vec<_>>()
+    };
+    let goal = (6, 0);
+    let stop = |state: &(usize, usize), _| match *state == goal {
+        true => Visit::Stop,
+        false => Visit::Continue,
+    };
+    let plain = dijkstra([(0, 0)], weighted, stop);
+    let guided = astar(
+        [(0, 0)],
+        weighted,
+        |&(x, y)| x.abs_diff(goal.0) + y.abs_diff(goal.1),
+        stop,
+    );
+    assert_eq!(plain.goal_cost(), Some(10));
+    assert_eq!(guided.goal_cost(), Some(10));
+    assert!(guided.visited <= plain.visited);
+}
@@ -0,0 +1,292 @@
+//! Drawing of grid puzzles, a board of chars with coloured layers of cells on top, as ANSI
+//! terminal output, SVG or PNG
+
+use std::collections::HashMap;
+use std::fmt::Write;
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(any(feature = "fs", feature = "png"))]
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use ndarray::Array2;
+
+use crate::utils::grid::Position;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const RED: Rgb = Rgb(230, 70, 60);
+    pub const ORANGE: Rgb = Rgb(240, 150, 40);
+    pub const YELLOW: Rgb = Rgb(240, 210, 60);
+    pub const GREEN: Rgb = Rgb(90, 200, 90);
+    pub const BLUE: Rgb = Rgb(80, 150, 240);
+
+    const BACKGROUND: Rgb = Rgb(20, 20, 20);
+
+    fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    fn ansi(self) -> String {
+        format!("\x1b[38;2;{};{};{}m", self.0, self.1, self.2)
+    }
+}
+
+/// Colour of a board cell no layer covers, walls stand out and empty floor fades away
+fn board_color(c: char) -> Rgb {
+    match c {
+        '#' => Rgb(110, 110, 110),
+        '.' | ' ' => Rgb(50, 50, 50),
+        _ => Rgb(170, 170, 170),
+    }
+}
+
+/// Cells to highlight, like a path or the reachable plots
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub name: String,
+    pub color: Rgb,
+    /// Drawn instead of the board's char
+    pub glyph: Option<char>,
+    pub cells: Vec<Position>,
+}
+
+/// A board with layers on top, later layers cover earlier ones
+#[derive(Debug, Clone)]
+pub struct Scene {
+    pub board: Array2<char>,
+    pub layers: Vec<Layer>,
+}
+
+impl Scene {
+    pub fn new(board: Array2<char>) -> Self {
+        Self {
+            board,
+            layers: Vec::new(),
+        }
+    }
+
+    pub fn layer(
+        mut self,
+        name: &str,
+        color: Rgb,
+        glyph: Option<char>,
+        cells: impl IntoIterator<Item = Position>,
+    ) -> Self {
+        self.layers.push(Layer {
+            name: name.to_string(),
+            color,
+            glyph,
+            cells: cells.into_iter().collect(),
+        });
+        self
+    }
+
+    /// The topmost layer covering each cell
+    fn covering(&self) -> HashMap<Position, &Layer> {
+        self.layers
+            .iter()
+            .flat_map(|layer| layer.cells.iter().map(move |pos| (*pos, layer)))
+            .collect()
+    }
+
+    /// Every cell with its char, colour and whether a layer covers it, row by row
+    fn cells(&self) -> impl Iterator<Item = (Position, char, Rgb, bool)> + '_ {
+        let covering = self.covering();
+        self.board.indexed_iter().map(move |(ix, c)| {
+            let pos = Position::from_index(ix);
+            match covering.get(&pos) {
+                Some(layer) => (pos, layer.glyph.unwrap_or(*c), layer.color, true),
+                None => (pos, *c, board_color(*c), false),
+            }
+        })
+    }
+
+    /// The board coloured with 24 bit ANSI escapes, followed by a legend of the layers
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        let mut current = None;
+        for (pos, c, color, _) in self.cells() {
+            if pos.x == 0 && pos.y > 0 {
+                out.push_str("\x1b[0m\n");
+                current = None;
+            }
+            if current != Some(color) {
+                out.push_str(&color.ansi());
+                current = Some(color);
+            }
+            out.push(c);
+        }
+        out.push_str("\x1b[0m\n");
+
+        let legend = self
+            .layers
+            .iter()
+            .map(|layer| {
+                format!(
+                    "{}{}\x1b[0m {} ({})",
+                    layer.color.ansi(),
+                    layer.glyph.unwrap_or('█'),
+                    layer.name,
+                    layer.cells.len()
+                )
+            })
+            .collect::<Vec<_>>();
+        out.push_str(&legend.join("  "));
+        out
+    }
+
+    /// Walls and covered cells as `cell_size` squares filled with their colour, with the chars on
+    /// top of them
+    pub fn to_svg(&self, cell_size: usize) -> String {
+        let (height, width) = self.board.dim();
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"monospace\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">\n<rect width=\"{w}\" height=\"{h}\" fill=\"{}\"/>\n",
+            cell_size * 4 / 5,
+            Rgb::BACKGROUND.hex(),
+            w = width * cell_size,
+            h = height * cell_size,
+        );
+        for (pos, c, color, covered) in self.cells() {
+            let (x, y) = (pos.x as usize * cell_size, pos.y as usize * cell_size);
+            let filled = covered || c == '#';
+            if filled {
+                writeln!(
+                    out,
+                    "<rect x=\"{x}\" y=\"{y}\" width=\"{cell_size}\" height=\"{cell_size}\" fill=\"{}\"/>",
+                    color.hex()
+                )
+                .unwrap();
+            }
+            let escaped = match c {
+                '.' | ' ' => continue,
+                '&' => "&amp;".to_string(),
+                '<' => "&lt;".to_string(),
+                '>' => "&gt;".to_string(),
+                c => c.to_string(),
+            };
+            writeln!(
+                out,
+                "<text x=\"{}\" y=\"{}\" fill=\"{}\">{escaped}</text>",
+                x + cell_size / 2,
+                y + cell_size / 2,
+                if filled { Rgb::BACKGROUND } else { color }.hex()
+            )
+            .unwrap();
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// Every cell as a `cell_size` square filled with its colour, PNGs have no room for the chars
+    #[cfg(feature = "png")]
+    pub fn to_png(&self, cell_size: usize) -> io::Result<Vec<u8>> {
+        let (height, width) = self.board.dim();
+        let row_len = width * cell_size * 3;
+        let mut pixels = vec![0; row_len * height * cell_size];
+        for (pos, _, color, _) in self.cells() {
+            let (x, y) = (pos.x as usize * cell_size, pos.y as usize * cell_size);
+            for row in y..y + cell_size {
+                let start = row * row_len + x * 3;
+                for pixel in pixels[start..start + cell_size * 3].chunks_mut(3) {
+                    pixel.copy_from_slice(&[color.0, color.1, color.2]);
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(
+            &mut out,
+            (width * cell_size) as u32,
+            (height * cell_size) as u32,
+        );
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&pixels)?;
+        Ok(out)
+    }
+
+    /// Writes the scene as SVG or PNG depending on the extension of `path`
+    #[cfg(feature = "fs")]
+    pub fn save(&self, path: &Path, cell_size: usize) -> io::Result<()> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("svg") => fs::write(path, self.to_svg(cell_size)),
+            #[cfg(feature = "png")]
+            Some("png") => fs::write(path, self.to_png(cell_size)?),
+            #[cfg(not(feature = "png"))]
+            Some("png") => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "PNG output needs the `png` feature",
+            )),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "expected a .svg or .png file",
+            )),
+        }
+    }
+}
+
+#[test]
+fn render_scene() {
+    let board = crate::utils::grid::parse_grid("#..\n.#<\n...", Some)
+        .unwrap()
+        .into_cells();
+    let scene = Scene::new(board)
+        .layer(
+            "path",
+            Rgb::YELLOW,
+            None,
+            [Position::new(1, 0), Position::new(2, 0)],
+        )
+        .layer("end", Rgb::RED, Some('E'), [Position::new(2, 0)]);
+
+    let ansi = scene.to_ansi();
+    let mut lines = ansi.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "\x1b[38;2;110;110;110m#\x1b[38;2;240;210;60m.\x1b[38;2;230;70;60mE\x1b[0m"
+    );
+    assert_eq!(
+        lines.nth(1).unwrap(),
+        "\x1b[38;2;50;50;50m...\x1b[0m",
+        "a colour is only set when it changes"
+    );
+    assert_eq!(
+        lines.next().unwrap(),
+        "\x1b[38;2;240;210;60m█\x1b[0m path (2)  \x1b[38;2;230;70;60mE\x1b[0m end (1)"
+    );
+
+    let svg = scene.to_svg(10);
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"30\""));
+    assert!(svg.contains("<rect x=\"20\" y=\"0\" width=\"10\" height=\"10\" fill=\"#e6463c\"/>"));
+    assert_eq!(svg.matches("<text").count(), 4);
+    assert!(svg.contains("fill=\"#aaaaaa\">&lt;</text>"));
+
+    #[cfg(feature = "fs")]
+    {
+        let dir = std::env::temp_dir().join(format!("aoc-2023-visualize-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        scene.save(&dir.join("scene.svg"), 10).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("scene.svg")).unwrap(), svg);
+        assert_eq!(
+            scene.save(&dir.join("scene.bmp"), 10).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        #[cfg(feature = "png")]
+        {
+            scene.save(&dir.join("scene.png"), 4).unwrap();
+            let decoder = png::Decoder::new(fs::File::open(dir.join("scene.png")).unwrap());
+            let mut reader = decoder.read_info().unwrap();
+            let mut pixels = vec![0; reader.output_buffer_size()];
+            let info = reader.next_frame(&mut pixels).unwrap();
+            assert_eq!((info.width, info.height), (12, 12));
+            // Second row of pixels in the top right cell
+            let start = info.line_size + 8 * 3;
+            assert_eq!(pixels[start..start + 3], [230, 70, 60]);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,126 @@
+//! The JavaScript API of the `wasm32-unknown-unknown` build, with a function per day plus
+//! [`solve`], [`visualize`] and [`dot`] taking the day as a number. Malformed inputs and days
+//! without a solution are thrown as an `Error` with the message of the [`crate::parse::ParseError`]
+
+use wasm_bindgen::prelude::*;
+#[cfg(all(test, target_arch = "wasm32"))]
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::registry::{self, Day};
+
+/// The answers of a day, `undefined` for a part without a solution
+#[wasm_bindgen(getter_with_clone)]
+pub struct Answers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+fn get(day: u32) -> Result<&'static Day, JsError> {
+    registry::get(day).ok_or_else(|| JsError::new(&format!("day {day} is not solved")))
+}
+
+/// Both parts of `day`
+#[wasm_bindgen]
+pub fn solve(day: u32, input: &str) -> Result<Answers, JsError> {
+    let mut answers = get(day)?.solution.run(input, &[1, 2])?.into_iter();
+    Ok(Answers {
+        part1: answers.next().flatten(),
+        part2: answers.next().flatten(),
+    })
+}
+
+/// The board of a grid day with what the solutions found on top as SVG, `undefined` for days
+/// without a board
+#[wasm_bindgen]
+pub fn visualize(day: u32, input: &str, cell_size: usize) -> Result<Option<String>, JsError> {
+    let scene = get(day)?.solution.visualize(input)?;
+    Ok(scene.map(|scene| scene.to_svg(cell_size)))
+}
+
+/// The graph of days 19, 20, 23 and 25 in Graphviz DOT, `undefined` for the other days
+#[wasm_bindgen]
+pub fn dot(day: u32, input: &str) -> Result<Option<String>, JsError> {
+    Ok(get(day)?.solution.export_dot(input)?)
+}
+
+macro_rules! export_days {
+    ($($name:ident => $day:literal),+) => {
+        $(
+            #[doc = concat!("Both parts of day ", $day, ", the same as `solve(", $day, ", input)`")]
+            #[wasm_bindgen]
+            pub fn $name(input: &str) -> Result<Answers, JsError> {
+                solve($day, input)
+            }
+        )+
+    };
+}
+
+export_days!(
+    day1 => 1,
+    day2 => 2,
+    day3 => 3,
+    day4 => 4,
+    day5 => 5,
+    day6 => 6,
+    day7 => 7,
+    day8 => 8,
+    day9 => 9,
+    day10 => 10,
+    day11 => 11,
+    day12 => 12,
+    day13 => 13,
+    day14 => 14,
+    day15 => 15,
+    day16 => 16,
+    day17 => 17,
+    day18 => 18,
+    day19 => 19,
+    day20 => 20,
+    day21 => 21,
+    day22 => 22,
+    day23 => 23,
+    day24 => 24,
+    day25 => 25
+);
+
+#[cfg(test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn solves_example() {
+    let Ok(answers) = day6("Time:      7  15   30\nDistance:  9  40  200") else {
+        panic!("the example did not parse");
+    };
+    assert_eq!(answers.part1.as_deref(), Some("288"));
+    assert_eq!(answers.part2.as_deref(), Some("71503"));
+
+    let Ok(Some(svg)) = visualize(10, ".....\n.S-7.\n.|.|.\n.L-J.\n.....", 4) else {
+        panic!("day 10 has a board");
+    };
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\""));
+}
+
+#[cfg(test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn answers_past_32_bits() {
+    let plan = "R 6 (#70c710)\nD 5 (#0dc571)\nL 2 (#5713f0)\nD 2 (#d2c081)\nR 2 (#59c680)
+D 2 (#411b91)\nL 5 (#8ceee2)\nU 2 (#caa173)\nL 1 (#1b58a2)\nU 2 (#caa171)\nR 2 (#7807d2)
+U 3 (#a77fa3)\nL 2 (#015232)\nU 2 (#7a21e3)";
+    let Ok(answers) = day18(plan) else {
+        panic!("the example did not parse");
+    };
+    assert_eq!(answers.part2.as_deref(), Some("952408144115"));
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+#[wasm_bindgen_test]
+fn throws_parse_errors() {
+    let Err(err) = day2("Game 1: 3 blue\nGame 2: 1 purple") else {
+        panic!("purple is not a colour of the game");
+    };
+    let message = format!("{:?}", JsValue::from(err));
+    assert!(
+        message.contains("line 2: unknown color 'purple'"),
+        "{message}"
+    );
+}